@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+use toml::Value;
+
+use crate::{
+    cli::Args,
+    error::MyError,
+    languages::{CommandExists, Language, Languages},
+    question::{Answer, Question},
+};
+
+/// Written into every new project, `--answers` creates the same project again from it
+pub const FILE_NAME: &str = ".bootstrapper-answers.toml";
+
+const HEADER: &str = "\
+# Written by project-bootstrapper. Pass this file to `--answers` to create the
+# project again with the same answers, e.g. from a newer version of the template.
+";
+
+/// Everything that was answered when creating a project
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AnswersFile {
+    language: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    name: String,
+    /// Answers to the options of the language
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    options: BTreeMap<String, Value>,
+    /// Answers to the questions of the template
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    answers: BTreeMap<String, Value>,
+}
+
+/// The contents of the answers file for a project. Questions of the template that weren't asked
+/// because of their `when` are left out.
+pub fn contents(
+    project_name: &str,
+    language: &Language,
+    option_answers: &[Answer],
+    answers: &[Answer],
+) -> String {
+    let manifest = &language.manifest;
+    let template = match &language.kind {
+        CommandExists::NotExists(template) => Some(template.name()),
+        CommandExists::Exists(_) => None,
+    };
+
+    let file = AnswersFile {
+        language: language.name.clone(),
+        version: template.as_ref().and(manifest.version.clone()),
+        template,
+        name: project_name.to_string(),
+        options: language
+            .options
+            .iter()
+            .zip(option_answers)
+            .map(|(option, answer)| (option.name.clone(), value(option, answer)))
+            .collect(),
+        answers: manifest
+            .questions
+            .iter()
+            .zip(answers)
+            .enumerate()
+            .filter(|&(index, _)| manifest.is_asked(index, answers))
+            .map(|(_, (question, answer))| (question.name.clone(), value(question, answer)))
+            .collect(),
+    };
+
+    // Only strings, numbers and lists of them, which can always be written
+    let body = toml::to_string(&file).unwrap_or_default();
+    format!("{HEADER}{body}")
+}
+
+/// `answer` the way `--option` takes it, with several choices as a list
+fn value(question: &Question, answer: &Answer) -> Value {
+    match answer {
+        Answer::Bool(yes) => Value::Boolean(*yes),
+        Answer::Int(number) => Value::Integer(*number),
+        Answer::Multi(_) => Value::Array(
+            question
+                .picked(answer)
+                .into_iter()
+                .map(|label| Value::String(label.to_string()))
+                .collect(),
+        ),
+        Answer::Text(_) | Answer::Choice(_) => Value::String(question.value_string(answer)),
+    }
+}
+
+/// Fills in what `args` doesn't say yet from the answers file at `path`, as if it was passed on
+/// the command line. Answers for options and questions the language no longer has are skipped
+/// with a warning, so a project can be created again from a newer template.
+pub fn replay(path: &Path, args: &mut Args, languages: &Languages) -> Result<(), MyError> {
+    let origin = path.display().to_string();
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| MyError::Usage(format!("couldn't read the answers file {origin}: {e}")))?;
+    let file: AnswersFile = toml::from_str(&text).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        // Reported as wrong usage, it's not the languages file that needs fixing
+        MyError::Usage(MyError::config(&text, &origin, offset, e.message()).to_string())
+    })?;
+
+    let language = languages.get(&file.language).ok_or_else(|| {
+        MyError::Usage(format!(
+            "{origin} is for the language `{}`, which isn't configured",
+            file.language
+        ))
+    })?;
+    match args.language.as_deref().map(|name| languages.get(name)) {
+        Some(Some(given)) if !std::ptr::eq(given, language) => {
+            return Err(MyError::Usage(format!(
+                "{origin} is for {language}, not {given}"
+            )))
+        }
+        // An unknown language is reported like without `--answers`
+        Some(_) => {}
+        None => args.language = Some(file.language.clone()),
+    }
+    if args.name.is_none() {
+        args.name = Some(file.name.clone());
+    }
+
+    check_template(&file, language, &origin);
+
+    let mut overrides = Vec::new();
+    let recorded = file.options.iter().chain(&file.answers);
+    for (name, value) in recorded {
+        let known = language
+            .options
+            .iter()
+            .chain(&language.manifest.questions)
+            .any(|question| &question.name == name);
+        if !known {
+            eprintln!(
+                "warning: {language} has no option or question `{name}` anymore, ignoring its \
+                 answer in {origin}"
+            );
+            continue;
+        }
+
+        let value = match value {
+            Value::String(text) => text.clone(),
+            Value::Boolean(yes) => if *yes { "yes" } else { "no" }.to_string(),
+            Value::Integer(number) => number.to_string(),
+            Value::Array(values) => values
+                .iter()
+                .map(|value| value.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| invalid_answer(&origin, name))?
+                .join(","),
+            _ => return Err(invalid_answer(&origin, name)),
+        };
+        overrides.push(format!("{name}={value}"));
+    }
+
+    // What's given on the command line wins, it's applied last
+    overrides.append(&mut args.option);
+    args.option = overrides;
+
+    Ok(())
+}
+
+/// Points out when the answers were given to a different template than the one that's used now
+fn check_template(file: &AnswersFile, language: &Language, origin: &str) {
+    let CommandExists::NotExists(template) = &language.kind else {
+        return;
+    };
+    let version = &language.manifest.version;
+
+    let name = template.name();
+    if file
+        .template
+        .as_ref()
+        .is_some_and(|recorded| *recorded != name)
+    {
+        eprintln!(
+            "warning: {origin} was written for the template `{}`, {language} now uses `{name}`",
+            file.template.as_deref().unwrap_or_default()
+        );
+    } else if file.version != *version {
+        match (&file.version, version) {
+            (Some(recorded), Some(version)) => eprintln!(
+                "note: {origin} was written for version {recorded} of the template, it's now at \
+                 version {version}"
+            ),
+            _ => eprintln!("note: {origin} was written for a different version of the template"),
+        }
+    }
+}
+
+fn invalid_answer(origin: &str, name: &str) -> MyError {
+    MyError::Usage(format!(
+        "{origin}: the answer `{name}` has to be text, a number, true or false, or a list of text"
+    ))
+}
@@ -0,0 +1,89 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::{merge::OnConflict, usage::Order};
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0    the project was created
+  2    wrong usage
+  3    invalid project name
+  4    invalid languages file
+  5    a required program isn't installed
+  6    a command failed or couldn't be started
+  7    the project directory already exists
+  8    a template couldn't be rendered
+  9    some other I/O error
+  130  interrupted
+
+The exit status of a failed command is part of the error message.";
+
+/// Bootstraps a new project for one of the configured languages.
+///
+/// Without any flags an interactive wizard asks for everything that's needed.
+#[derive(Parser)]
+#[command(version, after_help = EXIT_CODES)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Name of the project, skips the name prompt
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Language of the project, skips the language picker
+    #[arg(short, long)]
+    pub language: Option<String>,
+
+    /// Answer to one of the language's options or its template's questions, e.g.
+    /// `--option kind=library`
+    #[arg(short, long = "option", value_name = "NAME=VALUE")]
+    pub option: Vec<String>,
+
+    /// Answers recorded in a project's .bootstrapper-answers.toml, to create it again without
+    /// asking anything. Flags that are given as well win over them.
+    #[arg(long, value_name = "FILE")]
+    pub answers: Option<PathBuf>,
+
+    /// Don't write .bootstrapper-answers.toml into the new project
+    #[arg(long)]
+    pub no_answers_file: bool,
+
+    /// Initialize a git repository and commit the new project
+    #[arg(long)]
+    pub git: bool,
+
+    /// Branch of the git repository [default: git's init.defaultBranch or main]
+    #[arg(long, value_name = "BRANCH", requires = "git")]
+    pub git_branch: Option<String>,
+
+    /// Print what would be created and run, without touching anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Leave whatever was created so far in place if creating the project fails
+    #[arg(long)]
+    pub keep_on_failure: bool,
+
+    /// What to do if the project directory already exists [default: abort, or ask when
+    /// interactive]
+    #[arg(long, value_enum, value_name = "POLICY")]
+    pub on_conflict: Option<OnConflict>,
+
+    /// Order of the language picker [default: `order` in the languages file, or configured]
+    #[arg(long, value_enum)]
+    pub order: Option<Order>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Shows whether the programs every language needs are installed, and their versions
+    Doctor,
+}
+
+impl Args {
+    /// Whether everything needed is known without asking the user
+    pub fn is_complete(&self) -> bool {
+        self.name.is_some() && self.language.is_some()
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt::Display, path::PathBuf, process::ExitStatus};
+
+use crate::manifest;
+
+#[derive(Debug)]
+pub enum MyError {
+    Io(std::io::Error),
+    /// Wrong command line usage that clap can't catch by itself
+    Usage(String),
+    Config {
+        origin: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    InvalidName {
+        name: String,
+        reason: String,
+    },
+    /// The program a step wants to run isn't installed
+    MissingToolchain {
+        program: String,
+    },
+    Spawn {
+        command: String,
+        error: std::io::Error,
+    },
+    CommandFailed {
+        command: String,
+        status: ExitStatus,
+        /// The last few lines the command wrote to stderr
+        stderr: String,
+    },
+    StepFailed {
+        step: usize,
+        total: usize,
+        description: String,
+        error: Box<MyError>,
+    },
+    Conflict {
+        path: PathBuf,
+        message: String,
+    },
+    Template {
+        file: String,
+        message: String,
+    },
+    GracefulShutdown,
+}
+
+impl MyError {
+    /// A `Config` error about what's at byte `offset` of `text`
+    pub fn config(text: &str, origin: &str, offset: usize, message: &str) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+
+        Self::Config {
+            origin: origin.to_string(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Exit code of the process for this kind of error, so scripts can tell them apart
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Usage(_) => 2,
+            Self::InvalidName { .. } => 3,
+            Self::Config { .. } => 4,
+            Self::MissingToolchain { .. } => 5,
+            // The command's own exit code could be mistaken for one of the others, the message
+            // has it instead
+            Self::CommandFailed { .. } | Self::Spawn { .. } => 6,
+            Self::Conflict { .. } => 7,
+            Self::Template { .. } => 8,
+            Self::Io(_) => 9,
+            Self::StepFailed { error, .. } => error.exit_code(),
+            // What shells use for a process killed by SIGINT
+            Self::GracefulShutdown => 130,
+        }
+    }
+
+    /// Prints the error for the user, call only once the terminal is back to normal
+    pub fn report(&self) {
+        eprintln!("error: {self}");
+
+        let mut error = self;
+        while let Self::StepFailed { error: inner, .. } = error {
+            error = inner;
+        }
+
+        match error {
+            Self::MissingToolchain { program } => {
+                eprintln!(
+                    "hint: install `{program}` or make sure it's in one of the PATH directories"
+                );
+            }
+            Self::CommandFailed { stderr, .. } if !stderr.is_empty() => {
+                eprintln!("its last output was:");
+                for line in stderr.lines() {
+                    eprintln!("  | {line}");
+                }
+            }
+            Self::Config { origin, .. } if origin.ends_with(manifest::FILE_NAME) => {
+                eprintln!("hint: fix the template's {}", manifest::FILE_NAME);
+            }
+            Self::Config { .. } => {
+                eprintln!(
+                    "hint: fix the languages file or remove it to use the built-in languages"
+                );
+            }
+            Self::Conflict { .. } => {
+                eprintln!("hint: pick a different name or pass --on-conflict");
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Display for MyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Usage(message) => write!(f, "{message}"),
+            Self::Config {
+                origin,
+                line,
+                column,
+                message,
+            } => write!(f, "{origin}:{line}:{column}: {message}"),
+            Self::InvalidName { name, reason } => {
+                write!(f, "`{name}` isn't a valid project name: {reason}")
+            }
+            Self::MissingToolchain { program } => write!(f, "`{program}` isn't installed"),
+            Self::Spawn { command, error } => write!(f, "couldn't run `{command}`: {error}"),
+            Self::CommandFailed {
+                command, status, ..
+            } => write!(f, "`{command}` failed with {status}"),
+            Self::StepFailed {
+                step,
+                total,
+                description,
+                error,
+            } => write!(f, "step {step}/{total} ({description}) failed: {error}"),
+            Self::Conflict { path, message } => write!(f, "{} {message}", path.display()),
+            Self::Template { file, message } => write!(f, "template file `{file}`: {message}"),
+            Self::GracefulShutdown => write!(f, "interrupted"),
+        }
+    }
+}
+
+impl From<std::io::Error> for MyError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
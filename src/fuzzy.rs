@@ -0,0 +1,122 @@
+/// How well a pattern matched some text, and which characters of the text matched it
+pub struct Match {
+    pub score: i64,
+    /// Indices of the matched characters, in order
+    pub positions: Vec<usize>,
+}
+
+const MATCH: i64 = 16;
+/// Bonus for a match right after the previous one
+const CONSECUTIVE: i64 = 24;
+/// Bonus for a match at the start of a word, so `gh` finds "GitHub" before "laugh"
+const WORD_START: i64 = 32;
+/// Bonus for the whole pattern matching at the very start of the text
+const PREFIX: i64 = 64;
+/// Penalty for every character skipped between two matches
+const GAP: i64 = 2;
+
+/// Matches the characters of `pattern` in order but not necessarily next to each other in
+/// `text`, ignoring case. Returns `None` if some character can't be matched.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Match> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let first = *pattern.first()?;
+
+    // Trying every place the first character could match is quadratic at worst, but names and
+    // patterns are short
+    (0..lower.len())
+        .filter(|&start| lower[start] == first)
+        .filter_map(|start| match_from(&pattern, &text, &lower, start))
+        .max_by_key(|found| found.score)
+}
+
+/// Greedily matches the rest of `pattern` after its first character matched at `start`
+fn match_from(pattern: &[char], text: &[char], lower: &[char], start: usize) -> Option<Match> {
+    let mut positions = vec![start];
+    let mut score = MATCH + bonus(text, start);
+    if start == 0 {
+        score += PREFIX;
+    }
+
+    for &c in &pattern[1..] {
+        let previous = *positions.last()?;
+        let next = (previous + 1..lower.len()).find(|&index| lower[index] == c)?;
+
+        score += MATCH + bonus(text, next);
+        if next == previous + 1 {
+            score += CONSECUTIVE;
+        } else {
+            score -= GAP * i64::try_from(next - previous - 1).unwrap_or(i64::MAX / 4);
+        }
+        positions.push(next);
+    }
+
+    Some(Match { score, positions })
+}
+
+fn bonus(text: &[char], index: usize) -> i64 {
+    let is_word_start = match index.checked_sub(1).map(|previous| text[previous]) {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && text[index].is_uppercase())
+        }
+    };
+
+    if is_word_start {
+        WORD_START
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match;
+
+    fn score(pattern: &str, text: &str) -> i64 {
+        fuzzy_match(pattern, text).unwrap().score
+    }
+
+    fn positions(pattern: &str, text: &str) -> Vec<usize> {
+        fuzzy_match(pattern, text).unwrap().positions
+    }
+
+    #[test]
+    fn prefix_beats_word_start_beats_scattered() {
+        let prefix = score("sta", "stack");
+        let word_start = score("sta", "haskell-stack");
+        let scattered = score("sta", "cosmetray");
+
+        assert!(prefix > word_start, "{prefix} <= {word_start}");
+        assert!(word_start > scattered, "{word_start} <= {scattered}");
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_eq!(positions("HS", "haskell"), [0, 2]);
+        assert_eq!(positions("hs", "HASKELL"), [0, 2]);
+        assert_eq!(score("cpp", "CPP"), score("cpp", "cpp"));
+    }
+
+    #[test]
+    fn no_match() {
+        assert!(fuzzy_match("xyz", "haskell").is_none());
+        // Every character has to match in order
+        assert!(fuzzy_match("lh", "haskell").is_none());
+        assert!(fuzzy_match("", "haskell").is_none());
+        assert!(fuzzy_match("h", "").is_none());
+    }
+
+    #[test]
+    fn positions_of_the_best_match() {
+        assert_eq!(positions("hkl", "Haskell"), [0, 3, 5]);
+        // Word starts make the later match better than the one right next to each other
+        assert_eq!(positions("gh", "laugh GitHub"), [6, 9]);
+        assert_eq!(positions("ml", "OCaml"), [3, 4]);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{fs, path::Path, process::Command as Cmd};
+
+use crate::{error::MyError, pipeline};
+
+/// Branch used when neither `--git-branch` nor git's `init.defaultBranch` says otherwise
+const FALLBACK_BRANCH: &str = "main";
+
+/// The branch new repositories should start on according to the user's git config
+pub fn default_branch() -> String {
+    Cmd::new("git")
+        .args(["config", "--get", "init.defaultBranch"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|branch| branch.trim().to_string())
+        .filter(|branch| !branch.is_empty())
+        .unwrap_or_else(|| FALLBACK_BRANCH.to_string())
+}
+
+/// Makes `dir` a git repository with everything in it committed.
+///
+/// A repository the language's tool already created (like `cargo new` does) is reused, and when
+/// `dir` is inside some other work tree nothing is done so repositories don't end up nested.
+/// A repository that existed before the project was created never gets here, see `Action::GitInit`.
+pub fn init(dir: &Path, branch: &str, gitignore: Option<&str>) -> Result<(), MyError> {
+    if !dir.join(".git").exists() {
+        if is_inside_work_tree(dir) {
+            eprintln!(
+                "{} is already inside a git repository, not creating another one",
+                dir.display()
+            );
+            return Ok(());
+        }
+
+        git(dir, &["init", "--quiet"])?;
+    }
+
+    // Only possible while there are no commits yet, which is exactly when it matters
+    if !has_commits(dir) {
+        git(
+            dir,
+            &["symbolic-ref", "HEAD", &format!("refs/heads/{branch}")],
+        )?;
+    }
+
+    let gitignore_path = dir.join(".gitignore");
+    if let Some(gitignore) = gitignore.filter(|_| !gitignore_path.exists()) {
+        fs::write(gitignore_path, gitignore)?;
+    }
+
+    git(dir, &["add", "--all"])?;
+    git(dir, &["commit", "--quiet", "--message", "Initial commit"])
+}
+
+fn is_inside_work_tree(dir: &Path) -> bool {
+    Cmd::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|output| output.status.success() && output.stdout.starts_with(b"true"))
+}
+
+fn has_commits(dir: &Path) -> bool {
+    Cmd::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<(), MyError> {
+    pipeline::run(Cmd::new("git").args(args).current_dir(dir))
+}
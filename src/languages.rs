@@ -0,0 +1,507 @@
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt::Display, fs, path::PathBuf};
+use toml::Spanned;
+
+use crate::{
+    error::MyError,
+    manifest::Manifest,
+    naming::NameRules,
+    pipeline::{Action, Step},
+    question::{self, Answer, Choice, Question, QuestionKind},
+    template::Template,
+    usage::{Order, Usage},
+};
+
+const BUILTIN_LANGUAGES: &str = include_str!("languages.toml");
+
+pub enum CommandExists {
+    Exists(Command),
+    NotExists(Template),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Command {
+    #[serde(rename = "program")]
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub automatic_new_folder: bool,
+}
+
+pub struct Language {
+    /// What `--language` selects it by
+    pub name: String,
+    pub display_name: String,
+    /// Shown next to the name in the picker
+    pub description: Option<String>,
+    /// Shown for the highlighted language in the picker
+    pub help: Option<String>,
+    /// Other names it can be selected and searched by
+    pub aliases: Vec<String>,
+    /// Only for searching, e.g. "functional"
+    pub tags: Vec<String>,
+    pub kind: CommandExists,
+    /// Written by `--git` unless the language's tool already created a `.gitignore`
+    pub gitignore: Option<String>,
+    pub name_rules: NameRules,
+    /// Asked for after picking the language, their answers add arguments to its command
+    pub options: Vec<Question>,
+    /// Steps run once the project directory exists, their directories are relative to it
+    pub steps: Vec<Step>,
+    /// The questions of its template, empty for languages with a command
+    pub manifest: Manifest,
+}
+
+impl Language {
+    /// The default answers to the options and to the questions of the template, with those given
+    /// as `name=value` in `overrides` replacing them
+    pub fn answers(&self, overrides: &[String]) -> Result<(Vec<Answer>, Vec<Answer>), MyError> {
+        let questions = || self.options.iter().chain(&self.manifest.questions);
+
+        for assignment in overrides {
+            let Some((name, _)) = assignment.split_once('=') else {
+                return Err(MyError::Usage(format!(
+                    "`--option {assignment}` has to look like NAME=VALUE"
+                )));
+            };
+            if questions().any(|question| question.name == name) {
+                continue;
+            }
+
+            let names: Vec<_> = questions().map(|question| question.name.as_str()).collect();
+            return Err(MyError::Usage(if names.is_empty() {
+                format!("{self} has no options, `{name}` can't be set")
+            } else {
+                format!(
+                    "{self} has no option `{name}`, expected one of: {}",
+                    names.join(", ")
+                )
+            }));
+        }
+
+        Ok((
+            question::answers(&self.options, overrides)?,
+            question::answers(&self.manifest.questions, overrides)?,
+        ))
+    }
+
+    /// The arguments the answers to the options add to the command, with `expand` applied to the
+    /// configured arguments but not to the answers
+    pub fn option_args(&self, answers: &[Answer], expand: impl Fn(&str) -> String) -> Vec<String> {
+        self.options
+            .iter()
+            .zip(answers)
+            .flat_map(|(option, answer)| option.args(answer, &expand))
+            .collect()
+    }
+
+    /// The programs that have to be installed to create a project, in the order they're run
+    pub fn required_programs(&self) -> Vec<&str> {
+        let mut programs = Vec::new();
+        if let CommandExists::Exists(command) = &self.kind {
+            programs.push(command.command.as_str());
+        }
+        for step in &self.steps {
+            if let Action::Run(argv) = &step.action {
+                programs.push(argv[0].as_str());
+            }
+        }
+
+        let mut seen = Vec::new();
+        programs.retain(|program| {
+            let first = !seen.contains(program);
+            seen.push(*program);
+            first
+        });
+        programs
+    }
+
+    fn is_called(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|alias| alias == name)
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name)
+    }
+}
+
+/// All known languages, in the order they're configured in
+pub struct Languages {
+    languages: Vec<Language>,
+    /// How the language menu is sorted unless `--order` says otherwise
+    pub order: Order,
+}
+
+impl Languages {
+    /// Finds a language by its name or one of its aliases
+    pub fn get(&self, name: &str) -> Option<&Language> {
+        self.languages
+            .iter()
+            .find(|language| language.is_called(name))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Language> {
+        self.languages.iter()
+    }
+
+    /// The languages sorted for the menu, ties keep the configured order
+    pub fn sorted(&self, order: Order, usage: &Usage) -> Vec<&Language> {
+        let mut languages: Vec<_> = self.languages.iter().collect();
+        match order {
+            Order::Configured => {}
+            Order::Alphabetical => {
+                languages.sort_by_cached_key(|language| language.display_name.to_lowercase())
+            }
+            Order::Recent => {
+                languages.sort_by_key(|language| std::cmp::Reverse(usage.last_used(&language.name)))
+            }
+            Order::Frequent => languages.sort_by_key(|language| {
+                std::cmp::Reverse((usage.count(&language.name), usage.last_used(&language.name)))
+            }),
+        }
+        languages
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    order: Order,
+    #[serde(default)]
+    language: Vec<LanguageEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LanguageEntry {
+    name: Spanned<String>,
+    display_name: Option<String>,
+    description: Option<String>,
+    help: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    command: Option<Command>,
+    template: Option<Spanned<String>>,
+    gitignore: Option<String>,
+    #[serde(default)]
+    name_rules: NameRules,
+    #[serde(default)]
+    options: Vec<Spanned<OptionEntry>>,
+    #[serde(default)]
+    steps: Vec<Spanned<StepEntry>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptionEntry {
+    name: String,
+    label: Option<String>,
+    help: Option<String>,
+    args: Option<Vec<String>>,
+    checked: Option<bool>,
+    choices: Option<Vec<ChoiceEntry>>,
+    #[serde(default)]
+    multiple: bool,
+    default: Option<String>,
+}
+
+/// A choice of an option as it's written in the config
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChoiceEntry {
+    label: String,
+    #[serde(default)]
+    args: Vec<String>,
+    /// Whether it starts out checked, only for `multiple` options
+    #[serde(default)]
+    checked: bool,
+}
+
+impl From<ChoiceEntry> for Choice {
+    fn from(entry: ChoiceEntry) -> Self {
+        Self {
+            label: entry.label,
+            args: entry.args,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StepEntry {
+    run: Option<Vec<String>>,
+    create_dir: Option<PathBuf>,
+    write: Option<PathBuf>,
+    contents: Option<String>,
+    #[serde(default)]
+    dir: PathBuf,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    continue_on_failure: bool,
+}
+
+/// Path of the user's language config, `$XDG_CONFIG_HOME/project-bootstrapper/languages.toml`
+pub fn config_path() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(
+        config_home
+            .join("project-bootstrapper")
+            .join("languages.toml"),
+    )
+}
+
+/// Loads the user's languages, or the built-in ones if there is no config file
+pub fn load() -> Result<Languages, MyError> {
+    match config_path() {
+        Some(path) if path.exists() => {
+            let text = fs::read_to_string(&path)?;
+            Source {
+                text: &text,
+                origin: path.display().to_string(),
+                templates_dir: path.parent().map(|dir| dir.join("templates")),
+            }
+            .parse()
+        }
+        _ => parse(BUILTIN_LANGUAGES, "<built-in>"),
+    }
+}
+
+/// Languages from the text of a languages file that can only use the built-in templates
+pub fn parse(text: &str, origin: &str) -> Result<Languages, MyError> {
+    Source {
+        text,
+        origin: origin.to_string(),
+        templates_dir: None,
+    }
+    .parse()
+}
+
+struct Source<'a> {
+    text: &'a str,
+    origin: String,
+    /// Where user templates referenced by name are looked up
+    templates_dir: Option<PathBuf>,
+}
+
+impl Source<'_> {
+    fn parse(&self) -> Result<Languages, MyError> {
+        let config: ConfigFile = toml::from_str(self.text).map_err(|e| {
+            let offset = e.span().map_or(0, |span| span.start);
+            self.error(offset, e.message())
+        })?;
+
+        let mut languages = Vec::<Language>::new();
+        for entry in config.language {
+            let offset = entry.name.span().start;
+            let name = entry.name.into_inner();
+
+            let kind = match (entry.command, entry.template) {
+                (Some(command), None) => CommandExists::Exists(command),
+                (None, Some(template)) => {
+                    let found = Template::find(template.get_ref(), self.templates_dir.as_deref());
+                    let template_name = template.get_ref();
+                    CommandExists::NotExists(found.ok_or_else(|| {
+                        self.error(
+                            template.span().start,
+                            &format!("template `{template_name}` doesn't exist"),
+                        )
+                    })?)
+                }
+                _ => {
+                    return Err(self.error(
+                        offset,
+                        "exactly one of `command` or `template` has to be set",
+                    ))
+                }
+            };
+
+            let manifest = match &kind {
+                CommandExists::NotExists(template) => template.manifest()?,
+                CommandExists::Exists(_) => Manifest::default(),
+            };
+
+            let steps = entry
+                .steps
+                .into_iter()
+                .map(|step| self.step(step))
+                .collect::<Result<_, _>>()?;
+
+            let mut options = Vec::<Question>::new();
+            for entry in entry.options {
+                let offset = entry.span().start;
+                if !matches!(kind, CommandExists::Exists(_)) {
+                    return Err(
+                        self.error(offset, "only languages with a `command` can have `options`")
+                    );
+                }
+
+                let option = self.option(entry)?;
+                if options.iter().any(|other| other.name == option.name) {
+                    return Err(self.error(
+                        offset,
+                        &format!("option `{}` is defined more than once", option.name),
+                    ));
+                }
+                options.push(option);
+            }
+
+            if let Some(taken) = std::iter::once(&name)
+                .chain(&entry.aliases)
+                .find(|name| languages.iter().any(|language| language.is_called(name)))
+            {
+                return Err(self.error(
+                    offset,
+                    &format!("a language called `{taken}` is defined more than once"),
+                ));
+            }
+
+            languages.push(Language {
+                display_name: entry.display_name.unwrap_or_else(|| name.clone()),
+                description: entry.description,
+                help: entry.help,
+                aliases: entry.aliases,
+                tags: entry.tags,
+                kind,
+                gitignore: entry.gitignore,
+                name_rules: entry.name_rules,
+                options,
+                steps,
+                manifest,
+                name,
+            });
+        }
+
+        if languages.is_empty() {
+            return Err(self.error(0, "no languages are defined"));
+        }
+
+        Ok(Languages {
+            languages,
+            order: config.order,
+        })
+    }
+
+    fn option(&self, entry: Spanned<OptionEntry>) -> Result<Question, MyError> {
+        let offset = entry.span().start;
+        let entry = entry.into_inner();
+
+        let error = |message: &str| Err(self.error(offset, message));
+
+        let kind = match (entry.args, entry.choices) {
+            (Some(args), None) if args.iter().any(|arg| arg.contains("{value}")) => {
+                if entry.checked.is_some() || entry.multiple {
+                    return error("a text option can only have `args` and a `default` text");
+                }
+                QuestionKind::Text {
+                    default: entry.default.unwrap_or_default(),
+                    validate: None,
+                    args,
+                }
+            }
+            (Some(args), None) => {
+                if entry.default.is_some() || entry.multiple {
+                    return error(
+                        "a checkbox only has `args` and `checked`, add `{value}` to the `args` \
+                         for a text option",
+                    );
+                }
+                QuestionKind::Bool {
+                    default: entry.checked.unwrap_or(false),
+                    args,
+                }
+            }
+            (None, Some(choices)) if choices.is_empty() => {
+                return error("`choices` can't be empty")
+            }
+            (None, Some(choices)) if entry.multiple => {
+                if entry.default.is_some() || entry.checked.is_some() {
+                    return error("the `choices` themselves are `checked` in a `multiple` option");
+                }
+                let defaults = choices.iter().map(|choice| choice.checked).collect();
+                QuestionKind::Multi {
+                    choices: choices.into_iter().map(Choice::from).collect(),
+                    defaults,
+                }
+            }
+            (None, Some(choices)) => {
+                if entry.checked.is_some() || choices.iter().any(|choice| choice.checked) {
+                    return error("only `multiple` options can have `checked` choices");
+                }
+                let default = match entry.default {
+                    Some(default) => {
+                        match choices.iter().position(|choice| choice.label == default) {
+                            Some(index) => index,
+                            None => {
+                                return error(&format!(
+                                    "`default` has to be the label of one of the `choices`, \
+                                 `{default}` isn't"
+                                ))
+                            }
+                        }
+                    }
+                    None => 0,
+                };
+                QuestionKind::Choice {
+                    choices: choices.into_iter().map(Choice::from).collect(),
+                    default,
+                }
+            }
+            _ => return error("an option has either `args` or `choices`"),
+        };
+
+        Ok(Question {
+            label: entry.label.unwrap_or_else(|| entry.name.clone()),
+            name: entry.name,
+            help: entry.help,
+            kind,
+            when: None,
+        })
+    }
+
+    fn step(&self, entry: Spanned<StepEntry>) -> Result<Step, MyError> {
+        let offset = entry.span().start;
+        let entry = entry.into_inner();
+
+        let action = match (entry.run, entry.create_dir, entry.write, entry.contents) {
+            (Some(argv), None, None, None) if !argv.is_empty() => Action::Run(argv),
+            (Some(_), None, None, None) => return Err(self.error(offset, "`run` can't be empty")),
+            (None, Some(path), None, None) => Action::CreateDir(path),
+            (None, None, Some(path), contents) => Action::WriteFile {
+                path,
+                contents: contents.unwrap_or_default(),
+            },
+            (_, _, None, Some(_)) => {
+                return Err(self.error(offset, "`contents` can only be used with `write`"))
+            }
+            _ => {
+                return Err(self.error(
+                    offset,
+                    "exactly one of `run`, `create_dir` or `write` has to be set",
+                ))
+            }
+        };
+
+        Ok(Step {
+            action,
+            dir: entry.dir,
+            env: entry.env,
+            continue_on_failure: entry.continue_on_failure,
+        })
+    }
+
+    fn error(&self, offset: usize, message: &str) -> MyError {
+        MyError::config(self.text, &self.origin, offset, message)
+    }
+}
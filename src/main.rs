@@ -1,225 +1,1093 @@
+use clap::Parser;
 use crossterm::{
-    cursor,
-    event::{Event, KeyCode},
-    execute,
-    style::{self, Stylize},
-    terminal::{self, disable_raw_mode, enable_raw_mode},
-};
-use std::{
-    collections::HashMap, env, fmt::Display, fs, io::Write, os::unix::process::CommandExt,
-    path::PathBuf, process::Command as Cmd,
+    event::{KeyCode, KeyModifiers},
+    style::{self, ContentStyle, StyledContent, Stylize},
 };
+use std::{io::IsTerminal, path::PathBuf};
 
-#[derive(Debug)]
-enum MyError {
-    Io(std::io::Error),
-    GracefulShutdown,
-}
-
-impl From<std::io::Error> for MyError {
-    fn from(e: std::io::Error) -> Self {
-        Self::Io(e)
-    }
-}
-
-enum CommandExists {
-    Exists(Command),
-    NotExists(Vec<&'static str>),
-}
-
-struct Command {
-    command: &'static str,
-    args: Vec<&'static str>,
-    automatic_new_folder: bool,
-}
-
-lazy_static::lazy_static! {
-    static ref LANGUAGES: HashMap<ProjectLanguage, CommandExists> = {
-        HashMap::from([
-            (ProjectLanguage::Rust, CommandExists::Exists(Command {
-                command: "cargo",
-                args: vec!["new"],
-                automatic_new_folder: true,
-            })),
-            (ProjectLanguage::Web, CommandExists::NotExists(vec!["index.html"])),
-            (ProjectLanguage::Cpp, CommandExists::NotExists(vec!["src", "main.cpp"])),
-            (ProjectLanguage::Ocaml, CommandExists::Exists(Command {
-                command: "dune",
-                args: vec!["init", "project"],
-                automatic_new_folder: true,
-            })),
-            (ProjectLanguage::Haskell, CommandExists::Exists(Command {
-                command: "cabal",
-                args: vec!["init"],
-                automatic_new_folder: false,
-            })),
-        ])
-    };
+use error::MyError;
+use fuzzy::fuzzy_match;
+use languages::{CommandExists, Language, Languages};
+use manifest::Manifest;
+use merge::OnConflict;
+use pipeline::Step;
+use question::{Answer, Question};
+use screen::{Frame, Screen};
+use template::Variables;
+use terminal::TerminalGuard;
+use toolchain::Toolchains;
+use transaction::{Transaction, Undone};
+use usage::Usage;
+use widgets::{Checklist, Confirm, Field, Form, Input, List, Outcome, Select, TextInput};
+
+mod answers;
+mod cli;
+mod error;
+mod fuzzy;
+mod git;
+mod languages;
+mod manifest;
+mod merge;
+mod naming;
+mod pipeline;
+mod project;
+mod question;
+mod screen;
+mod template;
+mod terminal;
+#[cfg(test)]
+mod test_dir;
+mod toolchain;
+mod transaction;
+mod usage;
+mod widgets;
+
+fn main() {
+    let args = cli::Args::parse();
+
+    if let Err(e) = terminal::install_handlers() {
+        eprintln!("warning: couldn't set up restoring the terminal on exit: {e}");
+    }
+
+    if let Err(e) = run(args) {
+        exit_program_gracefully(&e);
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-enum ProjectLanguage {
-    Rust,
-    Web,
-    Cpp,
-    Ocaml,
-    Haskell,
+/// Reports `error` and ends the process with its exit code. `exit` doesn't run destructors, so a
+/// terminal still set up by a guard is restored first, like dropping the guard would.
+fn exit_program_gracefully(error: &MyError) -> ! {
+    terminal::restore();
+    error.report();
+    std::process::exit(error.exit_code())
 }
 
-impl Display for ProjectLanguage {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Rust => write!(f, "rust"),
-            Self::Web => write!(f, "web"),
-            Self::Cpp => write!(f, "cpp"),
-            Self::Ocaml => write!(f, "ocaml"),
-            Self::Haskell => write!(f, "haskell"),
-        }
-    }
+/// Everything that's been decided about the project before anything gets created
+struct Prepared<'a> {
+    project_name: String,
+    project_dir: PathBuf,
+    language: &'a Language,
+    variables: Variables,
+    steps: Vec<Step>,
+    preview: Vec<String>,
 }
 
-fn main() {
-    let mut stdout = std::io::stdout();
+fn run(mut args: cli::Args) -> Result<(), MyError> {
+    let languages = languages::load()?;
+    let toolchains = Toolchains::detect(&languages);
 
-    // Setting up the terminal for better usability
-    execute!(stdout, terminal::EnterAlternateScreen).unwrap();
-    enable_raw_mode().unwrap();
+    if let Some(cli::Command::Doctor) = args.command {
+        toolchain::doctor(&languages, &toolchains);
+        return Ok(());
+    }
 
-    let project_name = match get_project_name(&mut stdout) {
-        Ok(name) => name,
-        Err(MyError::GracefulShutdown) => exit_program_gracefully(&mut stdout),
-        Err(MyError::Io(e)) => panic!("{e}"),
-    };
+    if let Some(path) = args.answers.clone() {
+        answers::replay(&path, &mut args, &languages)?;
+    }
 
-    let language = get_selected_language(&mut stdout).unwrap();
+    let language = args
+        .language
+        .as_deref()
+        .map(|name| {
+            languages.get(name).ok_or_else(|| {
+                let known: Vec<_> = languages
+                    .iter()
+                    .map(|language| language.name.as_str())
+                    .collect();
+                MyError::Usage(format!(
+                    "unknown language `{name}`, expected one of: {}",
+                    known.join(", ")
+                ))
+            })
+        })
+        .transpose()?;
 
-    let project_dir = std::env::current_dir().unwrap().join(&project_name);
-    match LANGUAGES.get(&language).unwrap() {
-        CommandExists::Exists(command) if command.automatic_new_folder => {
-            Cmd::new(command.command)
-                .args(&command.args)
-                .arg(&project_name)
-                .exec();
+    // Failing before anything is asked or created, a dry run only shows what would be run
+    if let Some(program) = language.and_then(|language| toolchains.missing(language)) {
+        if !args.dry_run {
+            return Err(MyError::MissingToolchain {
+                program: program.to_string(),
+            });
         }
-        CommandExists::Exists(command) => {
-            fs::create_dir(&project_dir).unwrap();
-            env::set_current_dir(&project_dir).unwrap();
+    }
+
+    if !args.is_complete() && !std::io::stdin().is_terminal() {
+        return Err(MyError::Usage(
+            "stdin is not a terminal, pass both --name and --language to run non-interactively"
+                .to_string(),
+        ));
+    }
 
-            Cmd::new(command.command)
-                .args(&command.args)
-                .arg(&project_name)
-                .exec();
+    let interactive = !args.is_complete();
+
+    // Setting up the terminal for better usability, it's returned to normal when dropped
+    let guard = interactive.then(TerminalGuard::enter).transpose()?;
+
+    let mut screen = Screen::new(std::io::stdout());
+    let prepared = prepare(
+        &mut screen,
+        &args,
+        &languages,
+        language,
+        &toolchains,
+        interactive,
+    );
+
+    // The wizard is over, the terminal has to be back to normal before an error is reported or
+    // any command runs
+    drop(guard);
+
+    let Prepared {
+        project_name,
+        project_dir,
+        language,
+        variables,
+        steps,
+        preview,
+    } = prepared?;
+
+    if args.dry_run {
+        println!("Creating {project_name} ({language}) would:");
+        for line in preview {
+            println!("{line}");
         }
-        CommandExists::NotExists(file) => {
-            fs::create_dir(&project_name).unwrap();
-            env::set_current_dir(&project_dir).unwrap();
+        return Ok(());
+    }
 
-            let mut file_copy = file.clone();
+    let interrupted = transaction::catch_interrupts()?;
 
-            let file_name = file_copy.pop().unwrap();
-            let path: PathBuf = file_copy.iter().collect();
-            fs::create_dir_all(&path).unwrap();
+    let mut transaction = Transaction::default();
+    transaction.record(&project_dir);
 
-            env::set_current_dir(project_dir.join(&path)).unwrap();
-            let mut file = fs::File::create(file_name).unwrap();
-            file.write_all(b"test").unwrap();
+    if let Err(e) = pipeline::run_steps(&steps, &variables, &mut transaction, &interrupted) {
+        if args.keep_on_failure {
+            eprintln!("Keeping everything created so far because of --keep-on-failure");
+        } else {
+            let undone = transaction.rollback();
+            if !undone.is_empty() {
+                eprintln!("Creating the project failed, rolled back:");
+                for undone in undone {
+                    match undone {
+                        Undone::Removed(path) => eprintln!("  removed {}", path.display()),
+                        Undone::Restored(path) => eprintln!("  restored {}", path.display()),
+                    }
+                }
+            }
         }
+
+        return Err(e);
     }
+    transaction.commit();
 
-    // Returning the terminal to the normal state
-    execute!(stdout, terminal::LeaveAlternateScreen).unwrap();
-    disable_raw_mode().unwrap();
+    // Loaded again rather than kept from the start, so other runs in the meantime aren't lost
+    let mut usage = Usage::load();
+    usage.record(&language.name);
+    if let Err(e) = usage.save() {
+        eprintln!("warning: couldn't save which languages were used: {e}");
+    }
 
     println!("Done!");
+    Ok(())
 }
 
-fn clear_screen(stdout: &mut std::io::Stdout) -> Result<(), MyError> {
-    execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+/// The pages of the interactive wizard, in order
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Language,
+    Options,
+    /// The questions of the language's template
+    Prompts,
+    Name,
+    Conflict,
+    Summary,
+}
 
-    crossterm::queue!(stdout, cursor::MoveTo(0, 0),)?;
+/// Asks for whatever wasn't given on the command line and plans the project creation, all while
+/// the terminal is set up for the TUI if `interactive`. Pages whose answer is already known are
+/// skipped, Esc goes back to the previous page that was shown with its answer kept.
+fn prepare<'a>(
+    screen: &mut Screen,
+    args: &cli::Args,
+    languages: &'a Languages,
+    mut language: Option<&'a Language>,
+    toolchains: &Toolchains,
+    interactive: bool,
+) -> Result<Prepared<'a>, MyError> {
+    let current_dir = std::env::current_dir()?;
+    let mut name = args.name.clone().unwrap_or_default();
+    // A name from the command line is only asked for if it isn't valid or was already taken
+    let mut ask_name = args.name.is_none();
+    let mut merge = None;
+    // Answers to the options and template questions of `options_of`, they start over when a
+    // different language is picked
+    let mut option_answers = Vec::new();
+    let mut answers = Vec::new();
+    let mut options_of: Option<&Language> = None;
 
-    stdout.flush()?;
+    let mut history = Vec::new();
+    let mut page = Page::Language;
+    loop {
+        let current = page;
+        let outcome = match page {
+            Page::Language if args.language.is_some() => None,
+            // Asked first since it decides which project names are valid
+            Page::Language => {
+                let order = args.order.unwrap_or(languages.order);
+                let languages = languages.sorted(order, &Usage::load());
+                Some(
+                    get_selected_language(screen, &languages, toolchains, language)?
+                        .map(|selected| language = Some(selected)),
+                )
+            }
+            Page::Options => {
+                let Some(language) = language else {
+                    page = Page::Language;
+                    continue;
+                };
+                if !options_of.is_some_and(|options_of| std::ptr::eq(options_of, language)) {
+                    (option_answers, answers) = language.answers(&args.option)?;
+                    options_of = Some(language);
+                }
 
-    Ok(())
-}
+                if interactive && !language.options.is_empty() {
+                    Some(get_options(screen, language, &mut option_answers)?)
+                } else {
+                    None
+                }
+            }
+            Page::Prompts => {
+                let Some(language) = language else {
+                    page = Page::Language;
+                    continue;
+                };
+
+                if interactive && !language.manifest.questions.is_empty() {
+                    Some(get_template_answers(
+                        screen,
+                        &language.manifest,
+                        &mut answers,
+                    )?)
+                } else {
+                    None
+                }
+            }
+            Page::Name => {
+                let Some(language) = language else {
+                    page = Page::Language;
+                    continue;
+                };
+                match language.name_rules.check(&name) {
+                    Ok(()) if !ask_name => None,
+                    // Letting the user fix it rather than starting over
+                    _ if interactive => Some(get_project_name(screen, &mut name, language)?),
+                    Ok(()) => None,
+                    Err(reason) => return Err(MyError::InvalidName { name, reason }),
+                }
+            }
+            Page::Conflict => {
+                let project_dir = current_dir.join(&name);
+                merge = None;
+                if !project_dir.exists() {
+                    None
+                } else if let Some(on_conflict) = args.on_conflict {
+                    merge::check(&project_dir, on_conflict)?;
+                    merge = Some(on_conflict);
+                    None
+                } else if interactive {
+                    match get_conflict_choice(screen, &project_dir)? {
+                        Outcome::Next(Some(on_conflict)) => {
+                            merge::check(&project_dir, on_conflict)?;
+                            merge = Some(on_conflict);
+                            Some(Outcome::Next(()))
+                        }
+                        Outcome::Next(None) => {
+                            // Picking a different name, as if going back to that page
+                            if history.last() == Some(&Page::Name) {
+                                history.pop();
+                            }
+                            ask_name = true;
+                            page = Page::Name;
+                            continue;
+                        }
+                        Outcome::Back => Some(Outcome::Back),
+                    }
+                } else {
+                    merge::check(&project_dir, OnConflict::Abort)?;
+                    None
+                }
+            }
+            Page::Summary => {
+                let Some(language) = language else {
+                    page = Page::Language;
+                    continue;
+                };
 
-fn get_project_name(stdout: &mut std::io::Stdout) -> Result<String, MyError> {
-    clear_screen(stdout)?;
+                let project_dir = current_dir.join(&name);
+                let options = project::Options {
+                    git_branch: args
+                        .git
+                        .then(|| args.git_branch.clone().unwrap_or_else(git::default_branch)),
+                    merge,
+                    option_answers: option_answers.clone(),
+                    answers_file: (!args.no_answers_file)
+                        .then(|| answers::contents(&name, language, &option_answers, &answers)),
+                };
+                let manifest = &language.manifest;
+                let described: Vec<_> = language
+                    .options
+                    .iter()
+                    .zip(&option_answers)
+                    .chain(
+                        manifest
+                            .questions
+                            .iter()
+                            .zip(&answers)
+                            .enumerate()
+                            .filter(|&(index, _)| manifest.is_asked(index, &answers))
+                            .map(|(_, asked)| asked),
+                    )
+                    .map(|(question, answer)| {
+                        let value = question.value_string(answer);
+                        let value = if value.is_empty() { "none" } else { &value };
+                        format!("{} {value}", widgets::heading(&question.label))
+                    })
+                    .collect();
+                let mut variables = template::variables(&name);
+                variables.extend(manifest.variables(&answers));
+                let steps = project::plan(&name, &project_dir, language, &options, &variables);
+                let preview = pipeline::preview(&steps, &variables)?;
 
-    let mut project_name = String::new();
-    while let Event::Key(key) = crossterm::event::read().unwrap() {
-        let project_name = &mut project_name;
-        match key.code {
-            KeyCode::Char('c') if key.modifiers == crossterm::event::KeyModifiers::CONTROL => {
-                return Err(MyError::GracefulShutdown)
+                if interactive && !args.dry_run {
+                    if let Outcome::Back = confirm_plan(
+                        screen,
+                        &name,
+                        language,
+                        &project_dir,
+                        (&options, &described),
+                        &preview,
+                    )? {
+                        page = history.pop().unwrap_or(Page::Summary);
+                        continue;
+                    }
+                }
+
+                return Ok(Prepared {
+                    project_name: name,
+                    project_dir,
+                    language,
+                    variables,
+                    steps,
+                    preview,
+                });
             }
-            KeyCode::Enter => return Ok(project_name.clone()),
-            KeyCode::Backspace => {
-                project_name.pop();
+        };
+
+        page = match outcome {
+            // The page wasn't shown, so going back skips it
+            None => next_page(current),
+            Some(Outcome::Next(())) => {
+                history.push(current);
+                next_page(current)
             }
-            KeyCode::Char(c) => project_name.push(c),
-            _ => {}
+            Some(Outcome::Back) => history.pop().unwrap_or(current),
+        };
+    }
+}
+
+fn next_page(page: Page) -> Page {
+    match page {
+        Page::Language => Page::Options,
+        Page::Options => Page::Prompts,
+        Page::Prompts => Page::Name,
+        Page::Name => Page::Conflict,
+        Page::Conflict | Page::Summary => Page::Summary,
+    }
+}
+
+/// Asks for a project name until it's one `language` accepts. What's typed is kept in `name`
+/// when going back too.
+fn get_project_name(
+    screen: &mut Screen,
+    name: &mut String,
+    language: &Language,
+) -> Result<Outcome<()>, MyError> {
+    let mut input = TextInput::new("Project name: ").with_value(name.as_str());
+    // Complaining about an empty name only makes sense once the user tried to submit it
+    let mut submitted = false;
+    loop {
+        let error = language.name_rules.check(input.value()).err();
+
+        let (line, column) = input.render(screen.width());
+        let mut frame = Frame::default();
+        frame.push(line);
+        if let Some(error) = error
+            .as_ref()
+            .filter(|_| submitted || !input.value().is_empty())
+        {
+            frame.push(widgets::error(format!(
+                "Not a valid name for {language}: {error}"
+            )));
         }
+        frame.cursor = Some((column, 0));
+        screen.draw(frame)?;
 
-        // FIXME: I don't like this .clone() and also cleaning screen on every key press is bad
-        clear_screen(stdout)?;
-        crossterm::queue!(stdout, style::Print(project_name.clone().white()))?;
+        match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => match key.code {
+                KeyCode::Enter if error.is_none() => {
+                    *name = input.value().to_string();
+                    return Ok(Outcome::Next(()));
+                }
+                KeyCode::Esc => {
+                    *name = input.value().to_string();
+                    return Ok(Outcome::Back);
+                }
+                KeyCode::Enter => submitted = true,
+                _ => {
+                    input.handle_key(key);
+                }
+            },
+            Some(Input::Paste(text)) => input.paste(&text),
+            None => {}
+        }
+    }
+}
+
+/// A language in the picker, with what of it matched the search
+struct Candidate<'a> {
+    language: &'a Language,
+    /// Matched characters of the display name
+    highlight: Vec<usize>,
+    /// The name, alias or tag that matched better than the display name, shown next to it
+    matched: Option<(&'a str, Vec<usize>)>,
+}
 
-        stdout.flush()?;
+/// The languages matching `search`, best matches first and otherwise in menu order
+fn search_languages<'a>(languages: &[&'a Language], search: &str) -> Vec<Candidate<'a>> {
+    if search.is_empty() {
+        return languages
+            .iter()
+            .map(|&language| Candidate {
+                language,
+                highlight: Vec::new(),
+                matched: None,
+            })
+            .collect();
     }
 
-    Ok(project_name)
+    let mut scored: Vec<_> = languages
+        .iter()
+        .filter_map(|&language| {
+            let display_name = fuzzy_match(search, &language.display_name);
+            let other = std::iter::once(&language.name)
+                .chain(&language.aliases)
+                .chain(&language.tags)
+                .filter_map(|text| Some((text.as_str(), fuzzy_match(search, text)?)))
+                .max_by_key(|(_, found)| found.score);
+
+            match (display_name, other) {
+                (Some(found), other)
+                    if other
+                        .as_ref()
+                        .is_none_or(|(_, other)| found.score >= other.score) =>
+                {
+                    Some((
+                        found.score,
+                        Candidate {
+                            language,
+                            highlight: found.positions,
+                            matched: None,
+                        },
+                    ))
+                }
+                (_, Some((text, found))) => Some((
+                    found.score,
+                    Candidate {
+                        language,
+                        highlight: Vec::new(),
+                        matched: Some((text, found.positions)),
+                    },
+                )),
+                (_, None) => None,
+            }
+        })
+        .collect();
+
+    // What's shown wins over an alias or tag that matches just as well
+    scored
+        .sort_by_key(|(score, candidate)| (std::cmp::Reverse(*score), candidate.matched.is_some()));
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
 }
 
-fn print_selection(stdout: &mut std::io::Stdout, selected: usize) -> Result<(), MyError> {
-    crossterm::queue!(stdout, style::Print("What language do you want to use?"))?;
+/// Splits `text` into pieces styled like `base`, with the characters at `positions` emphasized
+fn highlighted(text: &str, positions: &[usize], base: ContentStyle) -> screen::Line {
+    let mut line: screen::Line = Vec::new();
+    let mut current = String::new();
+    let mut current_highlighted = false;
 
-    for (index, language) in LANGUAGES.iter().enumerate() {
-        let language = language.0;
-        crossterm::queue!(
-            stdout,
-            // FIXME: handle possible errors
-            cursor::MoveTo(0, (index + 1).try_into().unwrap()),
-            style::PrintStyledContent(if index == selected {
-                format!("> {language}\n").yellow()
-            } else {
-                format!("  {language}\n").magenta()
-            })
-        )?;
+    for (index, c) in text.chars().enumerate() {
+        let is_highlighted = positions.contains(&index);
+        if is_highlighted != current_highlighted && !current.is_empty() {
+            line.push(emphasize(
+                std::mem::take(&mut current),
+                current_highlighted,
+                base,
+            ));
+        }
+        current_highlighted = is_highlighted;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        line.push(emphasize(current, current_highlighted, base));
     }
 
-    stdout.flush()?;
+    line
+}
 
-    Ok(())
+fn emphasize(text: String, emphasized: bool, base: ContentStyle) -> StyledContent<String> {
+    let text = StyledContent::new(base, text);
+    if emphasized {
+        text.bold().underlined()
+    } else {
+        text
+    }
+}
+
+/// The lines about the highlighted language shown below the list
+fn print_details(language: &Language, toolchains: &Toolchains, width: usize) -> Vec<screen::Line> {
+    let mut lines: Vec<screen::Line> = Vec::new();
+    let mut text = |text: String| {
+        for line in screen::wrap(&text, width) {
+            lines.push(vec![style::style(line)]);
+        }
+    };
+
+    if let Some(help) = &language.help {
+        text(help.trim_end().to_string());
+    }
+
+    match &language.kind {
+        CommandExists::Exists(command) => {
+            let mut argv = vec![command.command.as_str()];
+            argv.extend(command.args.iter().map(String::as_str));
+            text(format!("Runs: {}", argv.join(" ")));
+        }
+        CommandExists::NotExists(template) => {
+            let files = template.files().map(|files| {
+                files
+                    .iter()
+                    .map(|(path, _)| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            });
+            // Only the directory of a user template can fail to be read, and that would happen
+            // again when creating the project
+            text(format!("Creates: {}", files.unwrap_or_default()));
+        }
+    }
+
+    let programs: Vec<_> = language
+        .required_programs()
+        .into_iter()
+        .map(|program| match toolchains.path(program) {
+            Some(_) => program.to_string(),
+            None => format!("{program} (not found)"),
+        })
+        .collect();
+    text(if programs.is_empty() {
+        "Needs: nothing".to_string()
+    } else {
+        format!("Needs: {}", programs.join(", "))
+    });
+
+    lines
+}
+
+fn print_selection(
+    frame: &mut Frame,
+    (width, height): (usize, usize),
+    search: screen::Line,
+    candidates: &[Candidate],
+    toolchains: &Toolchains,
+    select: &mut Select,
+) {
+    frame.push(widgets::title("What language do you want to use?"));
+    frame.push(search);
+
+    if candidates.is_empty() {
+        frame.push(vec!["  No language matches".to_string().dark_grey()]);
+    }
+
+    let details = select
+        .selected()
+        .map(|selected| print_details(candidates[selected].language, toolchains, width))
+        .unwrap_or_default();
+    // The details take up at most half of the screen, they're left out if it's too small
+    let details: &[screen::Line] = if height >= 12 {
+        &details[..details.len().min(height / 2 - 1)]
+    } else {
+        &[]
+    };
+    // With a separator line above them
+    let details_height = if details.is_empty() {
+        0
+    } else {
+        details.len() + 1
+    };
+    let list_height = height.saturating_sub(2) - details_height;
+
+    // The rest of the screen below the question and the search
+    for index in select.visible(list_height) {
+        let candidate = &candidates[index];
+        let missing = toolchains.missing(candidate.language);
+        let focused = Some(index) == select.selected();
+        let marker = if focused { "> " } else { "  " };
+        let base = match missing {
+            Some(_) => ContentStyle::new().dark_grey(),
+            None => widgets::entry_style(focused),
+        };
+
+        let mut line = vec![StyledContent::new(base, marker.to_string())];
+        line.extend(highlighted(
+            &candidate.language.display_name,
+            &candidate.highlight,
+            base,
+        ));
+        if let Some((text, positions)) = &candidate.matched {
+            let dim = ContentStyle::new().dark_grey();
+            line.push(StyledContent::new(dim, " (".to_string()));
+            line.extend(highlighted(text, positions, dim));
+            line.push(StyledContent::new(dim, ")".to_string()));
+        }
+        if let Some(program) = missing {
+            line.push(format!(" ({program} not found)").red());
+        }
+        if let Some(description) = &candidate.language.description {
+            line.push(format!("  {description}").dark_grey());
+        }
+        frame.push(line);
+    }
+
+    if !details.is_empty() {
+        // Keeping the details at the bottom so they don't jump around while searching
+        while frame.lines.len() < height - details.len() - 1 {
+            frame.push(Vec::new());
+        }
+        frame.push(vec!["─".repeat(width).dark_grey()]);
+        for line in details {
+            frame.push(line.clone());
+        }
+    }
+}
+
+/// Lets the user pick one of `languages`, only those whose programs are all installed can be
+/// picked
+fn get_selected_language<'a>(
+    screen: &mut Screen,
+    languages: &[&'a Language],
+    toolchains: &Toolchains,
+    initial: Option<&Language>,
+) -> Result<Outcome<&'a Language>, MyError> {
+    let mut search = TextInput::new("Search: ");
+    let mut candidates = search_languages(languages, search.value());
+    let mut select = Select::new(candidates.len());
+    if let Some(index) = initial.and_then(|initial| {
+        candidates
+            .iter()
+            .position(|candidate| std::ptr::eq(candidate.language, initial))
+    }) {
+        select.select(index);
+    }
+    loop {
+        let (search_line, column) = search.render(screen.width());
+        let mut frame = Frame::default();
+        print_selection(
+            &mut frame,
+            (screen.width().into(), screen.height().into()),
+            search_line,
+            &candidates,
+            toolchains,
+            &mut select,
+        );
+        frame.cursor = Some((column, 1));
+        screen.draw(frame)?;
+
+        let previous_search = search.value().to_string();
+        match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => {
+                let control = key.modifiers.contains(KeyModifiers::CONTROL);
+                let alt = key.modifiers.contains(KeyModifiers::ALT);
+                match key.code {
+                    KeyCode::Enter => {
+                        if let Some(selected) = select.selected() {
+                            let language = candidates[selected].language;
+                            if toolchains.missing(language).is_none() {
+                                return Ok(Outcome::Next(language));
+                            }
+                        }
+                    }
+                    KeyCode::Esc if search.value().is_empty() => return Ok(Outcome::Back),
+                    KeyCode::Esc => search = TextInput::new("Search: "),
+                    // `j` and `k` move the selection until a search is started, after that
+                    // typing always searches and Ctrl-N and Ctrl-P move it instead
+                    KeyCode::Char('j' | 'k') if search.value().is_empty() && !control && !alt => {
+                        select.handle_key(key);
+                    }
+                    KeyCode::Char(_) if !control && !alt => {
+                        search.handle_key(key);
+                    }
+                    _ => {
+                        if !select.handle_key(key) {
+                            search.handle_key(key);
+                        }
+                    }
+                }
+            }
+            Some(Input::Paste(text)) => search.paste(&text),
+            None => {}
+        }
+
+        if search.value() != previous_search {
+            candidates = search_languages(languages, search.value());
+            select = Select::new(candidates.len());
+        }
+    }
 }
 
-fn get_selected_language(stdout: &mut std::io::Stdout) -> Result<ProjectLanguage, MyError> {
-    execute!(stdout, cursor::Hide).unwrap();
-    let mut selected = 0;
+/// Asks for the answers to the options of `language`, starting out with `answers` and leaving
+/// them there when going on or back
+fn get_options(
+    screen: &mut Screen,
+    language: &Language,
+    answers: &mut [Answer],
+) -> Result<Outcome<()>, MyError> {
+    let fields = language
+        .options
+        .iter()
+        .zip(answers.iter())
+        .map(|(option, answer)| question_field(option, answer))
+        .collect();
+    let mut form = Form::new(fields);
+    // Only shown once the user tried to continue
+    let mut error = None;
+
     loop {
-        clear_screen(stdout)?;
+        let mut frame = Frame::default();
+        frame.push(widgets::title(format!("Options for {language}:")));
+        frame.push(Vec::new());
+        let (lines, cursor) = form.render(screen.width());
+        frame.cursor = cursor.map(|(column, row)| (column, row + 2));
+        for line in lines {
+            frame.push(line);
+        }
+        if let Some(error) = &error {
+            frame.push(widgets::error(error));
+        }
+        frame.push(Vec::new());
+        if let Some(help) = language
+            .options
+            .get(form.focused())
+            .and_then(|option| option.help.as_ref())
+        {
+            for line in widgets::help(help, screen.width()) {
+                frame.push(line);
+            }
+            frame.push(Vec::new());
+        }
+        frame.push(widgets::hint(
+            "Tab to move, Space or Left/Right to change, Enter to continue, Esc to go back",
+        ));
+        screen.draw(frame)?;
 
-        print_selection(stdout, selected).unwrap();
+        let outcome = match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => match key.code {
+                KeyCode::Enter => Outcome::Next(()),
+                KeyCode::Esc => Outcome::Back,
+                _ => {
+                    form.handle_key(key);
+                    continue;
+                }
+            },
+            Some(Input::Paste(text)) => {
+                form.paste(&text);
+                continue;
+            }
+            None => continue,
+        };
+
+        error = None;
+        for ((option, field), answer) in language
+            .options
+            .iter()
+            .zip(form.fields())
+            .zip(&mut *answers)
+        {
+            match field_answer(option, field) {
+                Ok(parsed) => *answer = parsed,
+                Err(message) => {
+                    error.get_or_insert(format!("{} {message}", option.label));
+                }
+            }
+        }
+        if error.is_none() || matches!(outcome, Outcome::Back) {
+            return Ok(outcome);
+        }
+    }
+}
 
-        if let Event::Key(key) = crossterm::event::read().unwrap() {
-            match key.code {
-                KeyCode::Up => selected -= 1,
-                KeyCode::Down => selected += 1,
-                KeyCode::Enter => break,
-                _ => {}
+/// Asks the questions of a template one after the other, skipping those whose `when` doesn't
+/// hold, starting out with `answers` and leaving the answers there when going on or back
+fn get_template_answers(
+    screen: &mut Screen,
+    manifest: &Manifest,
+    answers: &mut [Answer],
+) -> Result<Outcome<()>, MyError> {
+    // The questions that were asked so far, for going back to them
+    let mut asked = Vec::new();
+    let mut index = 0;
+    while index < manifest.questions.len() {
+        if !manifest.is_asked(index, answers) {
+            index += 1;
+            continue;
+        }
+
+        match get_prompt_answer(screen, &manifest.questions[index], &mut answers[index])? {
+            Outcome::Next(()) => {
+                asked.push(index);
+                index += 1;
             }
+            Outcome::Back => match asked.pop() {
+                Some(previous) => index = previous,
+                None => return Ok(Outcome::Back),
+            },
         }
     }
 
-    execute!(stdout, cursor::Show).unwrap();
-    // FIXME: handle possible errors
-    Ok(*LANGUAGES.iter().nth(selected).unwrap().0)
+    Ok(Outcome::Next(()))
 }
 
-fn exit_program_gracefully(stdout: &mut std::io::Stdout) -> ! {
-    // Returning the terminal to the normal state
-    execute!(stdout, terminal::LeaveAlternateScreen).unwrap();
-    disable_raw_mode().unwrap();
-    println!("Done!");
-    std::process::exit(0)
+/// Asks a single question of a template as a form with just one field. The answer is kept in
+/// `answer` when going back too, as long as it's valid.
+fn get_prompt_answer(
+    screen: &mut Screen,
+    question: &Question,
+    answer: &mut Answer,
+) -> Result<Outcome<()>, MyError> {
+    let keys = match answer {
+        Answer::Bool(_) => "Space to change, ",
+        Answer::Choice(_) => "Space or Left/Right to change, ",
+        Answer::Multi(_) => "Space to check, ",
+        Answer::Text(_) | Answer::Int(_) => "",
+    };
+    let field = question_field(question, answer);
+    let mut form = Form::new(vec![field]);
+    // Complaining about the answer only makes sense once the user tried to submit it
+    let mut submitted = false;
+
+    loop {
+        let parsed = field_answer(question, &form.fields()[0]);
+
+        let mut frame = Frame::default();
+        let (lines, cursor) = form.render(screen.width());
+        frame.cursor = cursor;
+        for line in lines {
+            frame.push(line);
+        }
+        if let (Err(error), true) = (&parsed, submitted) {
+            frame.push(widgets::error(format!("The answer {error}")));
+        }
+        frame.push(Vec::new());
+        if let Some(help) = &question.help {
+            for line in widgets::help(help, screen.width()) {
+                frame.push(line);
+            }
+            frame.push(Vec::new());
+        }
+        frame.push(widgets::hint(&format!(
+            "{keys}Enter to continue, Esc to go back"
+        )));
+        screen.draw(frame)?;
+
+        match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => match key.code {
+                KeyCode::Enter => match parsed {
+                    Ok(parsed) => {
+                        *answer = parsed;
+                        return Ok(Outcome::Next(()));
+                    }
+                    Err(_) => submitted = true,
+                },
+                KeyCode::Esc => {
+                    if let Ok(parsed) = parsed {
+                        *answer = parsed;
+                    }
+                    return Ok(Outcome::Back);
+                }
+                _ => {
+                    form.handle_key(key);
+                }
+            },
+            Some(Input::Paste(text)) => form.paste(&text),
+            None => {}
+        }
+    }
+}
+
+/// A form field asking `question`, starting out with `answer`
+fn question_field(question: &Question, answer: &Answer) -> Field {
+    let label = question.label.clone();
+    let labels = || {
+        question
+            .choices()
+            .iter()
+            .map(|choice| choice.label.clone())
+            .collect()
+    };
+
+    match answer {
+        &Answer::Bool(checked) => Field::Checkbox { label, checked },
+        &Answer::Choice(selected) => Field::Choice {
+            label,
+            choices: labels(),
+            selected,
+        },
+        Answer::Multi(picked) => Field::Checklist {
+            label,
+            list: Checklist::new(labels(), picked.clone()),
+        },
+        Answer::Text(_) | Answer::Int(_) => Field::Text(
+            TextInput::new(format!("{} ", widgets::heading(&label)))
+                .with_value(question.value_string(answer)),
+        ),
+    }
+}
+
+/// The answer to `question` in `field`, typed text is checked by the question
+fn field_answer(question: &Question, field: &Field) -> Result<Answer, String> {
+    match field {
+        Field::Text(input) => question.parse_value(input.value()),
+        Field::Checkbox { checked, .. } => Ok(Answer::Bool(*checked)),
+        Field::Choice { selected, .. } => Ok(Answer::Choice(*selected)),
+        Field::Checklist { list, .. } => Ok(Answer::Multi(list.checked().to_vec())),
+    }
+}
+
+/// Asks what to do about `project_dir` already existing, `None` meaning a different name should
+/// be picked
+fn get_conflict_choice(
+    screen: &mut Screen,
+    project_dir: &std::path::Path,
+) -> Result<Outcome<Option<OnConflict>>, MyError> {
+    let mut choices = vec![("Pick a different name", None)];
+    if merge::is_empty_dir(project_dir) {
+        choices.push((
+            "Create the project in the existing empty directory",
+            Some(OnConflict::Empty),
+        ));
+    } else if project_dir.is_dir() {
+        choices.push((
+            "Merge into the existing directory, asking about every existing file",
+            Some(OnConflict::Ask),
+        ));
+    }
+
+    let mut list = List::new(
+        choices
+            .iter()
+            .map(|(choice, _)| choice.to_string())
+            .collect(),
+    );
+    loop {
+        let mut frame = Frame::default();
+        frame.push(widgets::title(format!(
+            "{} already exists.",
+            project_dir.display()
+        )));
+        frame.push(Vec::new());
+        for line in list.render(usize::from(screen.height()).saturating_sub(2)) {
+            frame.push(line);
+        }
+        screen.draw(frame)?;
+
+        match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => match key.code {
+                KeyCode::Enter => {
+                    return Ok(Outcome::Next(choices[list.selected().unwrap_or(0)].1))
+                }
+                KeyCode::Esc => return Ok(Outcome::Back),
+                _ => {
+                    list.handle_key(key);
+                }
+            },
+            Some(Input::Paste(_)) | None => {}
+        }
+    }
+}
+
+/// Shows everything that was decided and what is about to happen, and asks whether to go ahead
+/// or go back and change something
+fn confirm_plan(
+    screen: &mut Screen,
+    project_name: &str,
+    language: &Language,
+    project_dir: &std::path::Path,
+    (options, answers): (&project::Options, &[String]),
+    preview: &[String],
+) -> Result<Outcome<()>, MyError> {
+    let existing = match options.merge {
+        None => "doesn't exist yet",
+        Some(OnConflict::Empty) => "exists and is empty",
+        Some(OnConflict::Skip) => "exists, existing files are kept",
+        Some(OnConflict::Overwrite) => "exists, existing files are replaced",
+        Some(OnConflict::Ask) => "exists, asking about every existing file",
+        Some(OnConflict::Abort) => "exists",
+    };
+    let git = match &options.git_branch {
+        Some(branch) => format!("yes, on branch {branch}"),
+        None => "no".to_string(),
+    };
+    let mut summary = vec![
+        ("Name", project_name.to_string()),
+        ("Language", language.to_string()),
+        (
+            "Directory",
+            format!("{} ({existing})", project_dir.display()),
+        ),
+        ("Git", git),
+    ];
+    if !answers.is_empty() {
+        summary.push(("Options", answers.join(", ")));
+    }
+
+    let mut confirm = Confirm::new("Create the project?", true);
+    loop {
+        let mut frame = Frame::default();
+        for (label, value) in &summary {
+            frame.push(vec![
+                format!("{label:>9}: ").bold(),
+                style::style(value.clone()),
+            ]);
+        }
+        frame.push(Vec::new());
+        frame.push(vec![style::style("This will:".to_string())]);
+        for line in preview {
+            frame.push(vec![style::style(line.clone())]);
+        }
+        frame.push(Vec::new());
+        frame.push(confirm.render());
+        frame.push(widgets::hint(
+            "Enter to answer, No or Esc to go back and change something",
+        ));
+        screen.draw(frame)?;
+
+        match widgets::read_input(screen)? {
+            Some(Input::Key(key)) => match key.code {
+                KeyCode::Enter if confirm.yes() => return Ok(Outcome::Next(())),
+                KeyCode::Enter | KeyCode::Esc => return Ok(Outcome::Back),
+                _ => {
+                    confirm.handle_key(key);
+                }
+            },
+            Some(Input::Paste(_)) | None => {}
+        }
+    }
 }
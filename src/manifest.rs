@@ -0,0 +1,575 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+use toml::{Spanned, Value};
+
+use crate::{
+    error::MyError,
+    question::{self, Answer, Choice, Condition, Question, QuestionKind},
+};
+
+/// The file in a template directory declaring its questions, it isn't copied into the project
+pub const FILE_NAME: &str = "template.toml";
+
+/// Variables every template has without asking for them
+const BUILTIN_VARIABLES: &[&str] = &["project_name", "crate_name", "author", "year"];
+
+/// What a template asks for besides the project name
+#[derive(Default)]
+pub struct Manifest {
+    /// Recorded in the answers file, to tell which version of the template a project came from
+    pub version: Option<String>,
+    pub questions: Vec<Question>,
+}
+
+impl Manifest {
+    /// Reads the manifest of the template in `dir`, which doesn't need to have one
+    pub fn load(dir: &Path) -> Result<Self, MyError> {
+        let path = dir.join(FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(&path)?;
+        parse(&text, &path.display().to_string())
+    }
+
+    /// Whether the question at `index` is asked, given the `answers` to the ones before it
+    pub fn is_asked(&self, index: usize, answers: &[Answer]) -> bool {
+        question::is_asked(&self.questions, index, answers)
+    }
+
+    /// The template variables for `answers`, questions that weren't asked are empty
+    pub fn variables(&self, answers: &[Answer]) -> Vec<(String, String)> {
+        self.questions
+            .iter()
+            .zip(answers)
+            .enumerate()
+            .map(|(index, (question, answer))| {
+                let value = if self.is_asked(index, answers) {
+                    question.value_string(answer)
+                } else {
+                    String::new()
+                };
+                (question.name.clone(), value)
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    version: Option<String>,
+    #[serde(default)]
+    prompt: Vec<Spanned<PromptEntry>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PromptEntry {
+    name: String,
+    question: String,
+    help: Option<String>,
+    #[serde(rename = "type", default)]
+    kind: PromptType,
+    default: Option<Spanned<Value>>,
+    validate: Option<Spanned<String>>,
+    choices: Option<Vec<String>>,
+    min: Option<i64>,
+    max: Option<i64>,
+    when: Option<Spanned<String>>,
+}
+
+#[derive(Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PromptType {
+    #[default]
+    String,
+    Bool,
+    Choice,
+    Multi,
+    Int,
+}
+
+fn parse(text: &str, origin: &str) -> Result<Manifest, MyError> {
+    let error = |offset: usize, message: &str| MyError::config(text, origin, offset, message);
+
+    let file: ManifestFile = toml::from_str(text).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        error(offset, e.message())
+    })?;
+
+    let mut questions = Vec::<Question>::new();
+    for entry in file.prompt {
+        let offset = entry.span().start;
+        let entry = entry.into_inner();
+
+        if entry.name.is_empty()
+            || !entry
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(error(
+                offset,
+                "a prompt's `name` can only have letters, digits and underscores",
+            ));
+        }
+        if BUILTIN_VARIABLES.contains(&entry.name.as_str()) {
+            return Err(error(
+                offset,
+                &format!("`{}` is already a variable of every template", entry.name),
+            ));
+        }
+        if questions.iter().any(|question| question.name == entry.name) {
+            return Err(error(
+                offset,
+                &format!("prompt `{}` is defined more than once", entry.name),
+            ));
+        }
+
+        let misplaced = if entry.validate.is_some() && entry.kind != PromptType::String {
+            Some("validate")
+        } else if entry.choices.is_some()
+            && !matches!(entry.kind, PromptType::Choice | PromptType::Multi)
+        {
+            Some("choices")
+        } else if (entry.min.is_some() || entry.max.is_some()) && entry.kind != PromptType::Int {
+            Some("min` and `max")
+        } else {
+            None
+        };
+        if let Some(key) = misplaced {
+            return Err(error(
+                offset,
+                &format!("`{key}` doesn't apply to this `type` of prompt"),
+            ));
+        }
+
+        let default_offset = entry.default.as_ref().map_or(offset, |d| d.span().start);
+        let default = entry.default.map(Spanned::into_inner);
+        let wrong_default = |expected: &str| {
+            Err(error(
+                default_offset,
+                &format!("the `default` of this prompt has to be {expected}"),
+            ))
+        };
+
+        let choices: Vec<_> = match (&entry.kind, entry.choices) {
+            (PromptType::Choice | PromptType::Multi, None) => {
+                return Err(error(offset, "this `type` of prompt needs `choices`"))
+            }
+            (_, Some(choices)) if choices.is_empty() => {
+                return Err(error(offset, "`choices` can't be empty"))
+            }
+            (_, choices) => choices
+                .unwrap_or_default()
+                .into_iter()
+                .map(|label| Choice {
+                    label,
+                    args: Vec::new(),
+                })
+                .collect(),
+        };
+        let find = |label: &str| choices.iter().position(|choice| choice.label == label);
+
+        let kind = match entry.kind {
+            PromptType::String => {
+                let validate = entry
+                    .validate
+                    .map(|validate| {
+                        // Checked by itself first so errors point into what was written
+                        Regex::new(validate.get_ref())
+                            .and_then(|_| Regex::new(&format!("^(?:{})$", validate.get_ref())))
+                            .map_err(|e| {
+                                error(validate.span().start, &format!("invalid `validate`: {e}"))
+                            })
+                    })
+                    .transpose()?;
+                let default = match default {
+                    None => String::new(),
+                    Some(Value::String(default)) => default,
+                    Some(_) => return wrong_default("a string"),
+                };
+                QuestionKind::Text {
+                    default,
+                    validate,
+                    args: Vec::new(),
+                }
+            }
+            PromptType::Bool => QuestionKind::Bool {
+                default: match default {
+                    None => false,
+                    Some(Value::Boolean(default)) => default,
+                    Some(_) => return wrong_default("true or false"),
+                },
+                args: Vec::new(),
+            },
+            PromptType::Choice => {
+                let default = match default {
+                    None => 0,
+                    Some(Value::String(label)) => match find(&label) {
+                        Some(index) => index,
+                        None => return wrong_default("one of the `choices`"),
+                    },
+                    Some(_) => return wrong_default("one of the `choices`"),
+                };
+                QuestionKind::Choice { choices, default }
+            }
+            PromptType::Multi => {
+                let mut defaults = vec![false; choices.len()];
+                for label in match default {
+                    None => Vec::new(),
+                    Some(Value::Array(labels)) => labels,
+                    Some(_) => return wrong_default("a list of `choices`"),
+                } {
+                    match label.as_str().and_then(find) {
+                        Some(index) => defaults[index] = true,
+                        None => return wrong_default("a list of `choices`"),
+                    }
+                }
+                QuestionKind::Multi { choices, defaults }
+            }
+            PromptType::Int => {
+                if entry.min.zip(entry.max).is_some_and(|(min, max)| min > max) {
+                    return Err(error(offset, "`min` can't be more than `max`"));
+                }
+                let default = match default {
+                    // Zero unless that's out of range
+                    None => 0
+                        .max(entry.min.unwrap_or(i64::MIN))
+                        .min(entry.max.unwrap_or(i64::MAX)),
+                    Some(Value::Integer(default)) => default,
+                    Some(_) => return wrong_default("a whole number"),
+                };
+                QuestionKind::Int {
+                    default,
+                    min: entry.min,
+                    max: entry.max,
+                }
+            }
+        };
+
+        let when = entry
+            .when
+            .map(|when| {
+                Condition::parse(when.get_ref(), &questions)
+                    .map_err(|message| error(when.span().start, &message))
+            })
+            .transpose()?;
+
+        let question = Question {
+            name: entry.name,
+            label: entry.question,
+            help: entry.help,
+            kind,
+            when,
+        };
+        // Texts and numbers can be out of what `validate`, `min` and `max` allow
+        if let QuestionKind::Text { .. } | QuestionKind::Int { .. } = question.kind {
+            let default = question.value_string(&question.default_value());
+            if let Err(message) = question.parse_value(&default) {
+                return Err(error(default_offset, &format!("the `default` {message}")));
+            }
+        }
+        questions.push(question);
+    }
+
+    Ok(Manifest {
+        version: file.version,
+        questions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Manifest};
+    use crate::question::{self, Answer};
+
+    fn manifest(text: &str) -> Manifest {
+        match parse(text, FILE) {
+            Ok(manifest) => manifest,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn error(text: &str) -> String {
+        match parse(text, FILE) {
+            Ok(_) => panic!("parsed even though it's invalid:\n{text}"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    const FILE: &str = "template.toml";
+
+    /// The answers with `overrides` applied and the variables for them
+    fn variables(manifest: &Manifest, overrides: &[&str]) -> Vec<(String, String)> {
+        let overrides: Vec<_> = overrides.iter().map(|o| o.to_string()).collect();
+        let answers = question::answers(&manifest.questions, &overrides).unwrap();
+        manifest.variables(&answers)
+    }
+
+    fn variable<'a>(variables: &'a [(String, String)], name: &str) -> &'a str {
+        &variables.iter().find(|(n, _)| n == name).unwrap().1
+    }
+
+    #[test]
+    fn defaults() {
+        let manifest = manifest(
+            r#"
+            version = "1.2"
+
+            [[prompt]]
+            name = "text"
+            question = "Text?"
+
+            [[prompt]]
+            name = "flag"
+            question = "Flag?"
+            type = "bool"
+
+            [[prompt]]
+            name = "pick"
+            question = "Pick?"
+            type = "choice"
+            choices = ["a", "b"]
+
+            [[prompt]]
+            name = "picked"
+            question = "Picked?"
+            type = "multi"
+            choices = ["a", "b", "c"]
+            default = ["a", "c"]
+
+            [[prompt]]
+            name = "number"
+            question = "Number?"
+            type = "int"
+            min = 3
+            "#,
+        );
+
+        assert_eq!(manifest.version.as_deref(), Some("1.2"));
+        let defaults: Vec<_> = manifest
+            .questions
+            .iter()
+            .map(|question| question.default_value())
+            .collect();
+        assert!(
+            defaults
+                == [
+                    Answer::Text(String::new()),
+                    Answer::Bool(false),
+                    Answer::Choice(0),
+                    Answer::Multi(vec![true, false, true]),
+                    // Zero would be less than `min`
+                    Answer::Int(3),
+                ]
+        );
+        assert_eq!(variable(&variables(&manifest, &[]), "picked"), "a, c");
+    }
+
+    #[test]
+    fn keys_have_to_fit_the_type() {
+        for (keys, message) in [
+            (r#"type = "bool""#, ""),
+            (
+                r#"type = "bool"
+                validate = "x""#,
+                "`validate` doesn't apply",
+            ),
+            (r#"choices = ["a"]"#, "`choices` doesn't apply"),
+            (
+                r#"type = "bool"
+                min = 1"#,
+                "`min` and `max` doesn't apply",
+            ),
+            (r#"type = "choice""#, "needs `choices`"),
+            (
+                r#"type = "multi"
+                choices = []"#,
+                "`choices` can't be empty",
+            ),
+            (
+                r#"type = "int"
+                min = 2
+                max = 1"#,
+                "`min` can't be more than `max`",
+            ),
+            (r#"type = "date""#, "unknown variant `date`"),
+            (r#"colour = "red""#, "unknown field `colour`"),
+        ] {
+            let text = format!("[[prompt]]\nname = \"x\"\nquestion = \"X?\"\n{keys}");
+            if message.is_empty() {
+                manifest(&text);
+            } else {
+                let error = error(&text);
+                assert!(error.contains(message), "{error:?} for:\n{text}");
+            }
+        }
+    }
+
+    #[test]
+    fn defaults_have_to_fit_the_type() {
+        for (keys, message) in [
+            (r#"default = 1"#, "has to be a string"),
+            (
+                r#"type = "bool"
+                default = "yes""#,
+                "has to be true or false",
+            ),
+            (
+                r#"type = "choice"
+                choices = ["a"]
+                default = "b""#,
+                "has to be one of the `choices`",
+            ),
+            (
+                r#"type = "multi"
+                choices = ["a"]
+                default = "a""#,
+                "has to be a list of `choices`",
+            ),
+            (
+                r#"type = "int"
+                default = "1""#,
+                "has to be a whole number",
+            ),
+            (
+                r#"type = "int"
+                max = 5
+                default = 6"#,
+                "the `default` can't be more than 5",
+            ),
+            (
+                r#"validate = "[a-z]+"
+                default = "A""#,
+                "the `default` has to match `[a-z]+`",
+            ),
+        ] {
+            let text = format!("[[prompt]]\nname = \"x\"\nquestion = \"X?\"\n{keys}");
+            let error = error(&text);
+            assert!(error.contains(message), "{error:?} for:\n{text}");
+        }
+    }
+
+    #[test]
+    fn names_have_to_be_new_variables() {
+        let prompt = |name: &str| format!("[[prompt]]\nname = \"{name}\"\nquestion = \"X?\"\n");
+
+        assert!(error(&prompt("my-name")).contains("letters, digits and underscores"));
+        assert!(error(&prompt("year")).contains("already a variable of every template"));
+        assert!(error(&format!("{}{}", prompt("x"), prompt("x"))).contains("more than once"));
+    }
+
+    #[test]
+    fn validate_matches_the_whole_answer() {
+        let manifest = manifest(
+            r#"
+            [[prompt]]
+            name = "word"
+            question = "Word?"
+            validate = "[a-z]+|[0-9]+"
+            default = "abc"
+            "#,
+        );
+        let question = &manifest.questions[0];
+
+        assert!(question.parse_value("abc").is_ok());
+        assert!(question.parse_value("123").is_ok());
+        // Without the anchors either alternative would match part of these
+        for invalid in ["abc1", "1abc", "ab c", ""] {
+            assert_eq!(
+                question.parse_value(invalid).err().as_deref(),
+                Some("has to match `[a-z]+|[0-9]+`"),
+                "{invalid:?}"
+            );
+        }
+
+        let error = error("[[prompt]]\nname = \"x\"\nquestion = \"X?\"\nvalidate = \"(\"");
+        assert!(error.contains("invalid `validate`"), "{error}");
+    }
+
+    const CONDITIONS: &str = r#"
+        [[prompt]]
+        name = "use_db"
+        question = "Database?"
+        type = "bool"
+
+        [[prompt]]
+        name = "database"
+        question = "Which?"
+        type = "choice"
+        choices = ["postgres", "sqlite"]
+        when = "use_db"
+
+        [[prompt]]
+        name = "path"
+        question = "Where?"
+        default = "db.sqlite"
+        when = "database == sqlite"
+
+        [[prompt]]
+        name = "host"
+        question = "Host?"
+        default = "localhost"
+        when = "database != sqlite"
+
+        [[prompt]]
+        name = "storage"
+        question = "Storage?"
+        default = "files"
+        when = "!use_db"
+    "#;
+
+    #[test]
+    fn when() {
+        let manifest = manifest(CONDITIONS);
+
+        let without = variables(&manifest, &[]);
+        assert_eq!(variable(&without, "use_db"), "false");
+        assert_eq!(variable(&without, "storage"), "files");
+
+        let sqlite = variables(&manifest, &["use_db=yes", "database=sqlite"]);
+        assert_eq!(variable(&sqlite, "database"), "sqlite");
+        assert_eq!(variable(&sqlite, "path"), "db.sqlite");
+
+        let postgres = variables(&manifest, &["use_db=yes", "database=postgres"]);
+        assert_eq!(variable(&postgres, "host"), "localhost");
+        assert_eq!(variable(&postgres, "storage"), "");
+    }
+
+    #[test]
+    fn questions_that_arent_asked_are_empty() {
+        let manifest = manifest(CONDITIONS);
+
+        // `database` isn't asked without a database, so its answer is left out even when one was
+        // given, and it counts as empty for the questions after it
+        let without = variables(&manifest, &["database=postgres"]);
+        assert_eq!(variable(&without, "database"), "");
+        assert_eq!(variable(&without, "path"), "");
+        assert_eq!(variable(&without, "host"), "localhost");
+
+        let sqlite = variables(&manifest, &["use_db=yes", "database=sqlite"]);
+        assert_eq!(variable(&sqlite, "host"), "");
+        assert_eq!(variable(&sqlite, "storage"), "");
+    }
+
+    #[test]
+    fn when_only_refers_to_earlier_questions() {
+        let error = error(
+            r#"
+            [[prompt]]
+            name = "a"
+            question = "A?"
+            when = "b"
+
+            [[prompt]]
+            name = "b"
+            question = "B?"
+            "#,
+        );
+        assert!(error.contains("`b` isn't"), "{error}");
+    }
+}
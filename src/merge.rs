@@ -0,0 +1,306 @@
+use clap::ValueEnum;
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command as Cmd,
+};
+
+use crate::{error::MyError, terminal::TerminalGuard, transaction::Transaction};
+
+/// What to do when the project directory already exists
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    /// Refuse to create the project
+    Abort,
+    /// Create the project in the directory only if it's empty
+    Empty,
+    /// Merge into the directory, keeping existing files
+    Skip,
+    /// Merge into the directory, replacing existing files
+    Overwrite,
+    /// Merge into the directory, asking about every existing file
+    #[value(skip)]
+    Ask,
+}
+
+#[derive(Clone, Copy)]
+enum Resolution {
+    Skip,
+    Overwrite,
+}
+
+/// Moves everything in `from` into `to`, resolving files that exist in both according to
+/// `on_conflict`. Files of `to` that are overwritten are moved into `backup` at the same relative
+/// path, so the transaction can put them back. `from` is removed afterwards.
+pub fn merge(
+    from: &Path,
+    to: &Path,
+    backup: &Path,
+    on_conflict: OnConflict,
+    transaction: &mut Transaction,
+) -> Result<(), MyError> {
+    fs::create_dir_all(to)?;
+    merge_dir(from, to, backup, on_conflict, transaction)?;
+    fs::remove_dir_all(from)?;
+
+    Ok(())
+}
+
+fn merge_dir(
+    from: &Path,
+    to: &Path,
+    backup: &Path,
+    on_conflict: OnConflict,
+    transaction: &mut Transaction,
+) -> Result<(), MyError> {
+    let mut entries = fs::read_dir(from)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        if !target.exists() {
+            transaction.record(&target);
+            fs::rename(&source, &target)?;
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            // Repositories aren't merged, the existing one is kept as it is
+            if entry.file_name() == ".git" {
+                continue;
+            }
+
+            if target.is_dir() {
+                let backup = backup.join(entry.file_name());
+                merge_dir(&source, &target, &backup, on_conflict, transaction)?;
+                continue;
+            }
+        }
+
+        resolve(
+            &source,
+            &target,
+            &backup.join(entry.file_name()),
+            on_conflict,
+            transaction,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Moves `source` to `target`, which already exists, if `on_conflict` says so. `target` is moved
+/// to `backup` first, so the transaction can put it back.
+pub fn resolve(
+    source: &Path,
+    target: &Path,
+    backup: &Path,
+    on_conflict: OnConflict,
+    transaction: &mut Transaction,
+) -> Result<(), MyError> {
+    let resolution = match on_conflict {
+        OnConflict::Skip => Resolution::Skip,
+        OnConflict::Overwrite => Resolution::Overwrite,
+        OnConflict::Ask => ask(source, target)?,
+        OnConflict::Abort | OnConflict::Empty => {
+            return Err(MyError::Conflict {
+                path: target.to_path_buf(),
+                message: "already exists".to_string(),
+            })
+        }
+    };
+
+    if let Resolution::Overwrite = resolution {
+        transaction.replace(target, backup)?;
+        fs::rename(source, target)?;
+    }
+
+    Ok(())
+}
+
+/// Asks what to do with `target` until the user decides to skip or overwrite it
+fn ask(source: &Path, target: &Path) -> Result<Resolution, MyError> {
+    loop {
+        print!(
+            "{} already exists: [s]kip, [o]verwrite or [d]iff? ",
+            target.display()
+        );
+        std::io::stdout().flush()?;
+
+        let key = read_key()?;
+        println!();
+
+        match key {
+            KeyCode::Char('s') => return Ok(Resolution::Skip),
+            KeyCode::Char('o') => return Ok(Resolution::Overwrite),
+            KeyCode::Char('d') => {
+                // `diff` exits with 1 when the files differ, that's not a failure here
+                if let Err(e) = Cmd::new("diff").arg("-u").arg(target).arg(source).status() {
+                    eprintln!("Couldn't run diff: {e}");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_key() -> Result<KeyCode, MyError> {
+    let _guard = TerminalGuard::raw()?;
+    loop {
+        if let Event::Key(key) = crossterm::event::read()? {
+            if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+                return Err(MyError::GracefulShutdown);
+            }
+            return Ok(key.code);
+        }
+    }
+}
+
+/// Checks whether the project can be created in the already existing `project_dir`
+pub fn check(project_dir: &Path, on_conflict: OnConflict) -> Result<(), MyError> {
+    let conflict = |message: &str| {
+        Err(MyError::Conflict {
+            path: project_dir.to_path_buf(),
+            message: message.to_string(),
+        })
+    };
+
+    if !project_dir.is_dir() {
+        return conflict("already exists and isn't a directory");
+    }
+
+    match on_conflict {
+        OnConflict::Abort => conflict("already exists, see --on-conflict"),
+        OnConflict::Empty if !is_empty_dir(project_dir) => {
+            conflict("already exists and isn't empty")
+        }
+        OnConflict::Empty | OnConflict::Skip | OnConflict::Overwrite | OnConflict::Ask => Ok(()),
+    }
+}
+
+/// Where the project is created first when it has to be merged into an existing directory
+pub fn staging_dir(project_dir: &Path) -> PathBuf {
+    let name = project_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let parent = project_dir.parent().unwrap_or(Path::new("."));
+
+    parent.join(format!(".{name}.bootstrapper-{}", std::process::id()))
+}
+
+/// Whether the directory exists and has nothing in it
+pub fn is_empty_dir(dir: &Path) -> bool {
+    fs::read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{merge, OnConflict};
+    use crate::{error::MyError, test_dir::TestDir, transaction::Transaction};
+
+    /// A project generated into `staging` and an existing `project` that it's merged into
+    fn setup() -> TestDir {
+        let dir = TestDir::new();
+        dir.write("staging/index.html", "generated");
+        dir.write("staging/src/main.js", "generated");
+        dir.write("staging/src/util.js", "generated");
+        dir.write("staging/.git/HEAD", "generated");
+        dir.write("project/index.html", "mine");
+        dir.write("project/src/main.js", "mine");
+        dir.write("project/notes.txt", "mine");
+        dir.write("project/.git/HEAD", "mine");
+        dir
+    }
+
+    fn read(dir: &TestDir, path: &str) -> String {
+        fs::read_to_string(dir.path().join(path)).unwrap()
+    }
+
+    fn merge_into_project(dir: &TestDir, on_conflict: OnConflict) -> Transaction {
+        let root = dir.path();
+        let mut transaction = Transaction::default();
+        transaction.remove_when_done(&root.join("backup"));
+        merge(
+            &root.join("staging"),
+            &root.join("project"),
+            &root.join("backup"),
+            on_conflict,
+            &mut transaction,
+        )
+        .unwrap();
+        transaction
+    }
+
+    #[test]
+    fn skip_keeps_existing_files() {
+        let dir = setup();
+        let transaction = merge_into_project(&dir, OnConflict::Skip);
+
+        assert_eq!(read(&dir, "project/index.html"), "mine");
+        assert_eq!(read(&dir, "project/src/main.js"), "mine");
+        assert_eq!(read(&dir, "project/src/util.js"), "generated");
+        assert_eq!(read(&dir, "project/.git/HEAD"), "mine");
+        assert!(!dir.path().join("staging").exists());
+
+        transaction.rollback();
+        assert!(!dir.path().join("project/src/util.js").exists());
+        assert_eq!(read(&dir, "project/index.html"), "mine");
+        assert_eq!(read(&dir, "project/notes.txt"), "mine");
+    }
+
+    #[test]
+    fn overwrite_is_undone_on_rollback() {
+        let dir = setup();
+        let transaction = merge_into_project(&dir, OnConflict::Overwrite);
+
+        assert_eq!(read(&dir, "project/index.html"), "generated");
+        assert_eq!(read(&dir, "project/src/main.js"), "generated");
+        // An existing repository is never replaced
+        assert_eq!(read(&dir, "project/.git/HEAD"), "mine");
+
+        transaction.rollback();
+        assert_eq!(read(&dir, "project/index.html"), "mine");
+        assert_eq!(read(&dir, "project/src/main.js"), "mine");
+        assert_eq!(read(&dir, "project/notes.txt"), "mine");
+        assert!(!dir.path().join("project/src/util.js").exists());
+        assert!(!dir.path().join("backup").exists());
+    }
+
+    #[test]
+    fn overwrite_is_kept_on_commit() {
+        let dir = setup();
+        merge_into_project(&dir, OnConflict::Overwrite).commit();
+
+        assert_eq!(read(&dir, "project/index.html"), "generated");
+        assert_eq!(read(&dir, "project/notes.txt"), "mine");
+        assert!(!dir.path().join("backup").exists());
+    }
+
+    #[test]
+    fn abort_stops_at_the_first_conflict() {
+        let dir = setup();
+        let root = dir.path();
+        let result = merge(
+            &root.join("staging"),
+            &root.join("project"),
+            &root.join("backup"),
+            OnConflict::Abort,
+            &mut Transaction::default(),
+        );
+
+        match result {
+            Err(MyError::Conflict { path, .. }) => {
+                assert_eq!(path, root.join("project/index.html"))
+            }
+            _ => panic!("merged despite the conflict"),
+        }
+        assert_eq!(read(&dir, "project/index.html"), "mine");
+    }
+}
@@ -0,0 +1,248 @@
+use serde::Deserialize;
+
+/// Which names a language's tooling accepts for a project, set with `name_rules` in the config
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameRules {
+    /// Anything that works as a directory name everywhere and needs no quoting in a shell
+    #[default]
+    Filesystem,
+    /// What `cargo new` accepts as a package name
+    Cargo,
+    /// What cabal accepts as a package name
+    Cabal,
+    /// What `dune init project` accepts, which also makes it a valid opam package name
+    Dune,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Characters a shell would do something with, or that aren't allowed in file names on Windows
+const SHELL_SPECIAL: &str = "/\\<>:\"'`|?*$;&()[]{}!#~";
+
+/// Names cargo refuses because they clash with the standard library or its own targets
+const CARGO_RESERVED: &[&str] = &["alloc", "core", "proc_macro", "proc-macro", "std", "test"];
+
+impl NameRules {
+    /// Returns why `name` can't be used, phrased to follow "isn't a valid project name: "
+    pub fn check(self, name: &str) -> Result<(), String> {
+        check_filesystem(name)?;
+
+        match self {
+            Self::Filesystem => Ok(()),
+            Self::Cargo => check_cargo(name),
+            Self::Cabal => check_cabal(name),
+            Self::Dune => check_dune(name),
+        }
+    }
+}
+
+fn check_filesystem(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("it's empty".to_string());
+    }
+    if name == "." || name == ".." {
+        return Err(format!("`{name}` is the name of a special directory"));
+    }
+    if name.starts_with('-') {
+        return Err("it starts with `-`, which tools would take for an option".to_string());
+    }
+    if name.len() > 255 {
+        return Err("it's longer than 255 bytes".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|&c| c.is_whitespace() || c.is_control() || SHELL_SPECIAL.contains(c))
+    {
+        return Err(format!("it contains {}", describe(c)));
+    }
+
+    Ok(())
+}
+
+fn check_cargo(name: &str) -> Result<(), String> {
+    if let Some(c) = name
+        .chars()
+        .find(|&c| !c.is_ascii_alphanumeric() && c != '-' && c != '_')
+    {
+        return Err(format!(
+            "it contains {}, Cargo package names can only use letters, digits, `-` and `_`",
+            describe(c)
+        ));
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err("Cargo package names can't start with a digit".to_string());
+    }
+
+    let crate_name = name.replace('-', "_");
+    if RUST_KEYWORDS.contains(&crate_name.as_str()) {
+        return Err(format!("`{crate_name}` is a Rust keyword"));
+    }
+    if CARGO_RESERVED.contains(&name) {
+        return Err(format!("`{name}` is reserved by Cargo"));
+    }
+
+    Ok(())
+}
+
+fn check_cabal(name: &str) -> Result<(), String> {
+    for component in name.split('-') {
+        if component.is_empty() {
+            return Err(
+                "cabal package names can't start or end with `-` or have two in a row".to_string(),
+            );
+        }
+        if let Some(c) = component.chars().find(|c| !c.is_ascii_alphanumeric()) {
+            return Err(format!(
+                "it contains {}, cabal package names can only use letters, digits and `-`",
+                describe(c)
+            ));
+        }
+        if !component.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Err(format!(
+                "`{component}` has no letters, every `-` separated part of a cabal package name \
+                 needs one"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_dune(name: &str) -> Result<(), String> {
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err("dune project names have to start with a letter".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|&c| !c.is_ascii_alphanumeric() && c != '_')
+    {
+        return Err(format!(
+            "it contains {}, dune project names can only use letters, digits and `_`",
+            describe(c)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Names a character so that invisible ones still show up in an error message
+fn describe(c: char) -> String {
+    if c == ' ' {
+        "a space".to_string()
+    } else if c.is_whitespace() || c.is_control() {
+        format!("the character {}", c.escape_default())
+    } else {
+        format!("`{c}`")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameRules;
+
+    fn check_all(rules: NameRules, cases: &[(&str, bool)]) {
+        for &(name, valid) in cases {
+            assert_eq!(
+                rules.check(name).is_ok(),
+                valid,
+                "`{name}` should be {}",
+                if valid { "valid" } else { "invalid" }
+            );
+        }
+    }
+
+    #[test]
+    fn filesystem() {
+        check_all(
+            NameRules::Filesystem,
+            &[
+                ("my-project", true),
+                ("My_Project.2", true),
+                ("héllo", true),
+                ("", false),
+                (".", false),
+                ("..", false),
+                ("-rf", false),
+                ("a b", false),
+                ("a\tb", false),
+                ("a/b", false),
+                ("a\\b", false),
+                ("a:b", false),
+                ("a*", false),
+                ("$HOME", false),
+                ("it's", false),
+                ("a;b", false),
+                ("a&b", false),
+                ("`id`", false),
+                ("a(b)", false),
+                (&"x".repeat(256), false),
+            ],
+        );
+    }
+
+    #[test]
+    fn cargo() {
+        check_all(
+            NameRules::Cargo,
+            &[
+                ("my-project", true),
+                ("my_project2", true),
+                ("fn-name", true),
+                ("2fast", false),
+                ("my.project", false),
+                ("fn", false),
+                ("Self", false),
+                ("async", false),
+                ("proc-macro", false),
+                ("std", false),
+                ("test", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn cabal() {
+        check_all(
+            NameRules::Cabal,
+            &[
+                ("my-package", true),
+                ("base64-bytes2", true),
+                ("x1-2a", true),
+                ("my_package", false),
+                ("-package", false),
+                ("package-", false),
+                ("my--package", false),
+                ("package-2", false),
+                ("123", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn dune() {
+        check_all(
+            NameRules::Dune,
+            &[
+                ("my_project", true),
+                ("Project2", true),
+                ("my-project", false),
+                ("_project", false),
+                ("2project", false),
+            ],
+        );
+    }
+
+    #[test]
+    fn filesystem_rules_apply_to_every_tool() {
+        for rules in [NameRules::Cargo, NameRules::Cabal, NameRules::Dune] {
+            check_all(rules, &[("", false), ("-project", false)]);
+        }
+    }
+}
@@ -0,0 +1,575 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process::{Command as Cmd, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{
+    answers,
+    error::MyError,
+    git,
+    merge::{self, OnConflict},
+    template::{self, Template, Variables},
+    transaction::Transaction,
+};
+
+#[derive(Clone)]
+pub enum Action {
+    /// Runs a program, the first element, with the rest as its arguments
+    Run(Vec<String>),
+    CreateDir(PathBuf),
+    WriteFile {
+        path: PathBuf,
+        contents: String,
+    },
+    Template(Template),
+    /// Writes the answers file into the step's directory, as is rather than as a template
+    WriteAnswers(String),
+    /// Moves the project created inside `staging` into the step's directory and removes `staging`
+    Merge {
+        staging: PathBuf,
+        on_conflict: OnConflict,
+    },
+    /// Initializes a git repository on the given branch and commits everything
+    GitInit {
+        branch: String,
+        gitignore: Option<String>,
+        /// Whether the step's directory was a repository before anything ran, its history and
+        /// uncommitted changes are the user's then and nothing is committed into it
+        existed: bool,
+    },
+}
+
+#[derive(Clone)]
+pub struct Step {
+    pub action: Action,
+    /// Working directory of the step, relative paths of the action are resolved against it
+    pub dir: PathBuf,
+    pub env: BTreeMap<String, String>,
+    pub continue_on_failure: bool,
+}
+
+impl Step {
+    pub fn new(action: Action, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            action,
+            dir: dir.into(),
+            env: BTreeMap::new(),
+            continue_on_failure: false,
+        }
+    }
+
+    /// Returns the step with its working directory resolved against the project directory
+    pub fn in_project(&self, project_dir: &Path) -> Self {
+        let dir = if self.dir.as_os_str().is_empty() {
+            project_dir.to_path_buf()
+        } else {
+            project_dir.join(&self.dir)
+        };
+
+        Self {
+            dir,
+            ..self.clone()
+        }
+    }
+
+    fn execute(
+        &self,
+        variables: &Variables,
+        transaction: &mut Transaction,
+        merged: &mut Option<Merged>,
+    ) -> Result<(), MyError> {
+        match &self.action {
+            Action::Run(argv) => {
+                let Some((program, args)) = argv.split_first() else {
+                    return Ok(());
+                };
+
+                let mut cmd = Cmd::new(program);
+                cmd.args(args).current_dir(&self.dir).envs(&self.env);
+                run(&mut cmd)
+            }
+            Action::CreateDir(path) => {
+                let path = self.dir.join(path);
+                transaction.record_dir_all(&path);
+                Ok(fs::create_dir_all(path)?)
+            }
+            Action::WriteFile { path, contents } => {
+                let path = self.dir.join(render_path(path, variables)?);
+                let contents = render(contents, &path, variables)?;
+                write_file(&path, contents, transaction, merged.as_ref())
+            }
+            Action::Template(template) => {
+                for (path, contents) in template.render_files(variables)? {
+                    write_file(&self.dir.join(path), contents, transaction, merged.as_ref())?;
+                }
+                Ok(())
+            }
+            Action::WriteAnswers(contents) => write_file(
+                &self.dir.join(answers::FILE_NAME),
+                contents,
+                transaction,
+                merged.as_ref(),
+            ),
+            Action::Merge {
+                staging,
+                on_conflict,
+            } => {
+                let into = Merged {
+                    dir: self.dir.clone(),
+                    staging: staging.clone(),
+                    on_conflict: *on_conflict,
+                };
+                merge::merge(
+                    &into.staged(""),
+                    &self.dir,
+                    &into.staged("replaced"),
+                    *on_conflict,
+                    transaction,
+                )?;
+                // Kept until the end, the replaced files in it are put back if a later step fails
+                transaction.remove_when_done(staging);
+                *merged = Some(into);
+                Ok(())
+            }
+            Action::GitInit {
+                branch,
+                gitignore,
+                existed,
+            } => {
+                if *existed {
+                    eprintln!(
+                        "{} was already a git repository, not committing into it",
+                        self.dir.display()
+                    );
+                    return Ok(());
+                }
+
+                let gitignore = gitignore
+                    .as_deref()
+                    .map(|gitignore| render(gitignore, &self.dir.join(".gitignore"), variables))
+                    .transpose()?;
+
+                transaction.record(&self.dir.join(".git"));
+                transaction.record(&self.dir.join(".gitignore"));
+                git::init(&self.dir, branch, gitignore.as_deref())
+            }
+        }
+    }
+}
+
+/// The project was merged into a directory that already existed
+struct Merged {
+    dir: PathBuf,
+    staging: PathBuf,
+    on_conflict: OnConflict,
+}
+
+impl Merged {
+    /// A directory inside the staging directory, named after the project with `suffix`, or where
+    /// the project was created in it if `suffix` is empty
+    fn staged(&self, suffix: &str) -> PathBuf {
+        let mut name = self.dir.file_name().unwrap_or_default().to_os_string();
+        if !suffix.is_empty() {
+            name.push(format!(".{suffix}"));
+        }
+        self.staging.join(name)
+    }
+}
+
+/// Writes the file, recording what's created. A file the project was merged over is resolved like
+/// the merged ones: it's kept, replaced with a backup or asked about, depending on the policy.
+fn write_file(
+    path: &Path,
+    contents: impl AsRef<[u8]>,
+    transaction: &mut Transaction,
+    merged: Option<&Merged>,
+) -> Result<(), MyError> {
+    if let Some(merged) = merged {
+        let existed = path.exists() && !transaction.is_recorded(path);
+        if let (true, Ok(relative)) = (existed, path.strip_prefix(&merged.dir)) {
+            let written = merged.staged("written").join(relative);
+            if let Some(parent) = written.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&written, contents)?;
+
+            let backup = merged.staged("replaced").join(relative);
+            return merge::resolve(&written, path, &backup, merged.on_conflict, transaction);
+        }
+    }
+
+    if let Some(parent) = path.parent() {
+        transaction.record_dir_all(parent);
+        fs::create_dir_all(parent)?;
+    }
+
+    transaction.record(path);
+    Ok(fs::write(path, contents)?)
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.action {
+            Action::Run(argv) => {
+                let env = self
+                    .env
+                    .iter()
+                    .map(|(key, value)| format!("{key}={}", quote(value)));
+                let argv = argv.iter().map(|arg| quote(arg).into_owned());
+                write!(f, "run `{}`", env.chain(argv).collect::<Vec<_>>().join(" "))?
+            }
+            Action::CreateDir(path) => write!(f, "create directory {}", path.display())?,
+            Action::WriteFile { path, .. } => write!(f, "write {}", path.display())?,
+            Action::Template(_) => write!(f, "render template")?,
+            Action::WriteAnswers(_) => write!(f, "write {}", answers::FILE_NAME)?,
+            Action::Merge { staging, .. } => {
+                let created = staging.join(self.dir.file_name().unwrap_or_default());
+                return write!(f, "move {} into {}", created.display(), self.dir.display());
+            }
+            Action::GitInit { branch, .. } => {
+                write!(f, "initialize git repository on branch {branch}")?
+            }
+        }
+
+        write!(f, " in {}", self.dir.display())
+    }
+}
+
+/// Describes what each step would do without doing any of it, as lines of text
+pub fn preview(steps: &[Step], variables: &Variables) -> Result<Vec<String>, MyError> {
+    let mut created = HashSet::new();
+    let mut lines = Vec::new();
+
+    for (index, step) in steps.iter().enumerate() {
+        lines.push(format!("{}. {step}", index + 1));
+
+        let mut files = Vec::new();
+        match &step.action {
+            Action::Run(_) => {}
+            Action::CreateDir(path) => {
+                missing_dirs(&step.dir.join(path), &mut created, &mut lines);
+            }
+            Action::WriteFile { path, contents } => {
+                let path = step.dir.join(render_path(path, variables)?);
+                let contents = render(contents, &path, variables)?;
+                files.push((path, contents.len()));
+            }
+            Action::Template(template) => {
+                for (path, contents) in template.render_files(variables)? {
+                    files.push((step.dir.join(path), contents.len()));
+                }
+            }
+            Action::WriteAnswers(contents) => {
+                files.push((step.dir.join(answers::FILE_NAME), contents.len()));
+            }
+            Action::Merge { on_conflict, .. } => lines.push(
+                match on_conflict {
+                    OnConflict::Skip => "   existing files are kept",
+                    OnConflict::Overwrite => "   existing files are overwritten",
+                    OnConflict::Ask => "   asks what to do with every existing file",
+                    OnConflict::Abort | OnConflict::Empty => "   fails if any file already exists",
+                }
+                .to_string(),
+            ),
+            Action::GitInit { existed: true, .. } => {
+                lines.push("   nothing, it's already a git repository".to_string());
+            }
+            Action::GitInit { gitignore, .. } => {
+                let path = step.dir.join(".gitignore");
+                if let Some(gitignore) = gitignore.as_deref().filter(|_| !created.contains(&path)) {
+                    let contents = render(gitignore, &path, variables)?;
+                    lines.push(format!(
+                        "   + {} ({} bytes, unless it exists)",
+                        path.display(),
+                        contents.len()
+                    ));
+                }
+                lines.push("   git init, unless already inside a repository".to_string());
+                lines.push("   git add --all && git commit".to_string());
+            }
+        }
+
+        for (path, size) in files {
+            if let Some(parent) = path.parent() {
+                missing_dirs(parent, &mut created, &mut lines);
+            }
+            lines.push(format!("   + {} ({size} bytes)", path.display()));
+            created.insert(path);
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Lists the directories that creating `dir` with all its parents would create
+fn missing_dirs(dir: &Path, created: &mut HashSet<PathBuf>, lines: &mut Vec<String>) {
+    let missing: Vec<_> = dir
+        .ancestors()
+        .take_while(|dir| !dir.exists() && !created.contains(*dir))
+        .map(Path::to_path_buf)
+        .collect();
+
+    for dir in missing.into_iter().rev() {
+        lines.push(format!("   + {}/", dir.display()));
+        created.insert(dir);
+    }
+}
+
+/// Quotes the argument for a shell if it needs to be
+fn quote(arg: &str) -> Cow<'_, str> {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+
+    if is_plain {
+        Cow::Borrowed(arg)
+    } else {
+        Cow::Owned(format!("'{}'", arg.replace('\'', r"'\''")))
+    }
+}
+
+fn render_path(path: &Path, variables: &Variables) -> Result<PathBuf, MyError> {
+    render(&path.to_string_lossy(), path, variables).map(PathBuf::from)
+}
+
+fn render(contents: &str, path: &Path, variables: &Variables) -> Result<String, MyError> {
+    template::render(contents, variables).map_err(|message| MyError::Template {
+        file: path.display().to_string(),
+        message,
+    })
+}
+
+/// Runs the steps in order, stopping at the first one that fails unless it's allowed to fail.
+///
+/// Everything the steps create is recorded in `transaction`, and no further steps are started
+/// once `interrupted` is set.
+pub fn run_steps(
+    steps: &[Step],
+    variables: &Variables,
+    transaction: &mut Transaction,
+    interrupted: &AtomicBool,
+) -> Result<(), MyError> {
+    let mut merged = None;
+    for (index, step) in steps.iter().enumerate() {
+        let result = step.execute(variables, transaction, &mut merged);
+
+        if interrupted.load(Ordering::SeqCst) {
+            return Err(MyError::GracefulShutdown);
+        }
+
+        let Err(error) = result else {
+            continue;
+        };
+
+        let error = MyError::StepFailed {
+            step: index + 1,
+            total: steps.len(),
+            description: step.to_string(),
+            error: Box::new(error),
+        };
+
+        if !step.continue_on_failure {
+            return Err(error);
+        }
+        eprintln!("{error}, continuing anyway");
+    }
+
+    Ok(())
+}
+
+/// How much of a command's stderr is kept around for the error message
+const STDERR_TAIL_LINES: usize = 10;
+
+/// Runs the command to completion, failing if it couldn't be started or didn't succeed. The
+/// command's stderr is still shown as it's written, but its end is also kept for the error.
+pub fn run(cmd: &mut Cmd) -> Result<(), MyError> {
+    let command = std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| quote(&arg.to_string_lossy()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut child = cmd.stderr(Stdio::piped()).spawn().map_err(|error| {
+        if error.kind() == io::ErrorKind::NotFound {
+            MyError::MissingToolchain {
+                program: cmd.get_program().to_string_lossy().into_owned(),
+            }
+        } else {
+            MyError::Spawn {
+                command: command.clone(),
+                error,
+            }
+        }
+    })?;
+
+    let mut stderr = Vec::new();
+    if let Some(mut child_stderr) = child.stderr.take() {
+        let mut buffer = [0; 4096];
+        loop {
+            match child_stderr.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    // Failing to show the output shouldn't fail the command
+                    let _ = io::stderr().write_all(&buffer[..read]);
+                    stderr.extend_from_slice(&buffer[..read]);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    if status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    let lines: Vec<_> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+
+    Err(MyError::CommandFailed {
+        command,
+        status,
+        stderr: tail,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, sync::atomic::AtomicBool};
+
+    use super::{run_steps, Action, Step};
+    use crate::{
+        answers, error::MyError, merge::OnConflict, template::Variables, test_dir::TestDir,
+        transaction::Transaction,
+    };
+
+    /// Creates a project into a staging directory, merges it into the existing `project` and
+    /// then writes more files into it, failing at the end if `fail`
+    fn steps(root: &Path, on_conflict: OnConflict, fail: bool) -> Vec<Step> {
+        let staging = root.join("staging");
+        let created = staging.join("project");
+        let project = root.join("project");
+        let write = |path: &str| Action::WriteFile {
+            path: path.into(),
+            contents: "generated".to_string(),
+        };
+
+        let mut steps = vec![
+            Step::new(Action::CreateDir(staging.clone()), root),
+            Step::new(Action::CreateDir(created.clone()), root),
+            Step::new(write("index.html"), &created),
+            Step::new(
+                Action::Merge {
+                    staging,
+                    on_conflict,
+                },
+                &project,
+            ),
+            Step::new(write("cabal.project"), &project),
+            Step::new(write("index.html"), &project),
+            Step::new(write("new.txt"), &project),
+            Step::new(Action::WriteAnswers("generated".to_string()), &project),
+        ];
+        if fail {
+            steps.push(Step::new(Action::Run(vec!["false".to_string()]), &project));
+        }
+        steps
+    }
+
+    /// An existing project directory with files every step above writes
+    fn existing() -> TestDir {
+        let dir = TestDir::new();
+        for file in ["index.html", "cabal.project", answers::FILE_NAME] {
+            dir.write(&format!("project/{file}"), "mine");
+        }
+        dir
+    }
+
+    fn run(
+        dir: &TestDir,
+        on_conflict: OnConflict,
+        fail: bool,
+    ) -> (Result<(), MyError>, Transaction) {
+        let mut transaction = Transaction::default();
+        let result = run_steps(
+            &steps(dir.path(), on_conflict, fail),
+            &Variables::new(),
+            &mut transaction,
+            &AtomicBool::new(false),
+        );
+        (result, transaction)
+    }
+
+    /// The contents of the files in the project
+    fn contents(dir: &TestDir) -> Vec<String> {
+        ["index.html", "cabal.project", answers::FILE_NAME, "new.txt"]
+            .iter()
+            .map(|file| {
+                fs::read_to_string(dir.path().join("project").join(file)).unwrap_or_default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn skip_keeps_files_later_steps_write() {
+        let dir = existing();
+        let (result, transaction) = run(&dir, OnConflict::Skip, false);
+
+        assert!(result.is_ok());
+        transaction.commit();
+        assert_eq!(contents(&dir), ["mine", "mine", "mine", "generated"]);
+        assert!(!dir.path().join("staging").exists());
+    }
+
+    #[test]
+    fn overwrite_replaces_files_later_steps_write() {
+        let dir = existing();
+        let (result, transaction) = run(&dir, OnConflict::Overwrite, false);
+
+        assert!(result.is_ok());
+        transaction.commit();
+        assert_eq!(
+            contents(&dir),
+            ["generated", "generated", "generated", "generated"]
+        );
+        assert!(!dir.path().join("staging").exists());
+    }
+
+    #[test]
+    fn rollback_restores_files_later_steps_overwrote() {
+        for on_conflict in [OnConflict::Skip, OnConflict::Overwrite] {
+            let dir = existing();
+            let (result, transaction) = run(&dir, on_conflict, true);
+
+            assert!(result.is_err());
+            transaction.rollback();
+            assert_eq!(contents(&dir), ["mine", "mine", "mine", ""]);
+            assert!(!dir.path().join("staging").exists());
+        }
+    }
+
+    #[test]
+    fn abort_fails_on_files_later_steps_write() {
+        let dir = TestDir::new();
+        dir.write("project/cabal.project", "mine");
+        let (result, transaction) = run(&dir, OnConflict::Abort, false);
+
+        match result {
+            Err(MyError::StepFailed { step: 5, error, .. }) => {
+                assert!(matches!(*error, MyError::Conflict { .. }))
+            }
+            _ => panic!("wrote over an existing file"),
+        }
+        transaction.rollback();
+        assert_eq!(contents(&dir), ["", "mine", "", ""]);
+    }
+}
@@ -0,0 +1,265 @@
+use std::path::Path;
+
+use crate::{
+    languages::{CommandExists, Language},
+    merge::{self, OnConflict},
+    pipeline::{Action, Step},
+    question::Answer,
+    template::Variables,
+};
+
+/// Choices about the project that aren't part of the language
+pub struct Options {
+    /// Branch to initialize a git repository on, no repository is created if unset
+    pub git_branch: Option<String>,
+    /// Set when the project directory already exists, the project is then created elsewhere and
+    /// merged into it
+    pub merge: Option<OnConflict>,
+    /// The answers to the language's options, which add arguments to its command
+    pub option_answers: Vec<Answer>,
+    /// Contents of the answers file written into the project, unless it's turned off
+    pub answers_file: Option<String>,
+}
+
+/// Every step needed to create the project in `project_dir`, in order
+pub fn plan(
+    project_name: &str,
+    project_dir: &Path,
+    language: &Language,
+    options: &Options,
+    variables: &Variables,
+) -> Vec<Step> {
+    // An existing directory can't be created into directly by most tools, so a fresh one is
+    // created next to it instead and the result merged in afterwards
+    let staging = options.merge.map(|_| merge::staging_dir(project_dir));
+    let create_dir = match &staging {
+        Some(staging) => staging.join(project_dir.file_name().unwrap_or_default()),
+        None => project_dir.to_path_buf(),
+    };
+    let parent = create_dir.parent().unwrap_or(Path::new("."));
+    // The command creating the project has to create it where it's merged from, later steps run
+    // in the project itself
+    let create_placeholders = placeholders(&create_dir, variables);
+    let placeholders = placeholders(project_dir, variables);
+
+    let mut steps = Vec::new();
+    if let Some(staging) = &staging {
+        let project_parent = project_dir.parent().unwrap_or(Path::new("."));
+        steps.push(Step::new(
+            Action::CreateDir(staging.clone()),
+            project_parent,
+        ));
+    }
+
+    match &language.kind {
+        CommandExists::Exists(command) => {
+            let mut argv = vec![command.command.clone()];
+            let mut uses_placeholders = false;
+            for arg in &command.args {
+                let (arg, expanded) = expand(arg, &create_placeholders);
+                argv.push(arg);
+                uses_placeholders |= expanded;
+            }
+            argv.extend(language.option_args(&options.option_answers, |arg| {
+                expand(arg, &create_placeholders).0
+            }));
+
+            // Commands that don't say where the name goes get it as the last argument
+            if !uses_placeholders {
+                argv.push(project_name.to_string());
+            }
+
+            if command.automatic_new_folder {
+                steps.push(Step::new(Action::Run(argv), parent));
+            } else {
+                steps.push(Step::new(Action::CreateDir(create_dir.clone()), parent));
+                steps.push(Step::new(Action::Run(argv), &create_dir));
+            }
+        }
+        CommandExists::NotExists(template) => {
+            steps.push(Step::new(Action::CreateDir(create_dir.clone()), parent));
+            steps.push(Step::new(Action::Template(template.clone()), &create_dir));
+        }
+    }
+
+    if let (Some(staging), Some(on_conflict)) = (staging, options.merge) {
+        steps.push(Step::new(
+            Action::Merge {
+                staging,
+                on_conflict,
+            },
+            project_dir,
+        ));
+    }
+
+    steps.extend(language.steps.iter().map(|step| {
+        let mut step = step.in_project(project_dir);
+        if let Action::Run(argv) = &mut step.action {
+            for arg in argv {
+                *arg = expand(arg, &placeholders).0;
+            }
+        }
+        step
+    }));
+
+    // Before initializing git, so it's part of the first commit
+    if let Some(contents) = &options.answers_file {
+        steps.push(Step::new(
+            Action::WriteAnswers(contents.clone()),
+            project_dir,
+        ));
+    }
+
+    if let Some(branch) = &options.git_branch {
+        steps.push(Step::new(
+            Action::GitInit {
+                branch: branch.clone(),
+                gitignore: language.gitignore.clone(),
+                existed: project_dir.join(".git").exists(),
+            },
+            project_dir,
+        ));
+    }
+
+    steps
+}
+
+/// Values of the `{name}`-style placeholders usable in command arguments
+fn placeholders(project_dir: &Path, variables: &Variables) -> Vec<(&'static str, String)> {
+    let dir = project_dir.display().to_string();
+    let variable = |name| variables.get(name).cloned().unwrap_or_default();
+
+    vec![
+        ("{name}", variable("project_name")),
+        ("{dir}", dir),
+        ("{crate_name}", variable("crate_name")),
+        ("{author}", variable("author")),
+    ]
+}
+
+/// Substitutes the known placeholders in `arg`, returning whether there were any. Anything else
+/// in braces, like a shell's `${VAR}`, is left alone.
+fn expand(arg: &str, placeholders: &[(&str, String)]) -> (String, bool) {
+    let mut arg = arg.to_string();
+    let mut expanded = false;
+
+    for (placeholder, value) in placeholders {
+        if arg.contains(placeholder) {
+            arg = arg.replace(placeholder, value);
+            expanded = true;
+        }
+    }
+
+    (arg, expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{expand, placeholders, plan, Options};
+    use crate::{languages, pipeline::Action, template::Variables};
+
+    const LANGUAGES: &str = r#"
+        [[language]]
+        name = "named"
+        display_name = "Named"
+        command = { program = "tool", args = ["new", "--name={name}"] }
+
+        [[language]]
+        name = "unnamed"
+        display_name = "Unnamed"
+        command = { program = "tool", args = ["init"] }
+
+        [[language.options]]
+        name = "synopsis"
+        label = "Synopsis"
+        args = ["--synopsis", "{value}"]
+
+        [[language.options]]
+        name = "module"
+        label = "Module"
+        args = ["--module={crate_name}.{value}"]
+
+        [[language.options]]
+        name = "license"
+        label = "License file"
+        args = ["--license-file={dir}/LICENSE"]
+    "#;
+
+    fn variables() -> Variables {
+        Variables::from([
+            ("project_name".to_string(), "my-app".to_string()),
+            ("crate_name".to_string(), "my_app".to_string()),
+            ("author".to_string(), "Ada".to_string()),
+        ])
+    }
+
+    /// The command `plan` runs for `language` with the options set by `overrides`
+    fn command(language: &str, overrides: &[&str]) -> Vec<String> {
+        let languages = languages::parse(LANGUAGES, "languages.toml").unwrap();
+        let language = languages.get(language).unwrap();
+        let overrides: Vec<_> = overrides.iter().map(|o| o.to_string()).collect();
+        let options = Options {
+            git_branch: None,
+            merge: None,
+            option_answers: language.answers(&overrides).unwrap().0,
+            answers_file: None,
+        };
+
+        let steps = plan(
+            "my-app",
+            Path::new("/work/my-app"),
+            language,
+            &options,
+            &variables(),
+        );
+        steps
+            .into_iter()
+            .find_map(|step| match step.action {
+                Action::Run(argv) => Some(argv),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn expands_known_placeholders() {
+        let placeholders = placeholders(Path::new("/work/my-app"), &variables());
+
+        assert_eq!(
+            expand("{dir}/{crate_name}-{name} by {author}", &placeholders),
+            ("/work/my-app/my_app-my-app by Ada".to_string(), true)
+        );
+        assert_eq!(
+            expand("${HOME}/{unknown}", &placeholders),
+            ("${HOME}/{unknown}".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn name_is_passed_last_unless_the_command_places_it() {
+        assert_eq!(command("named", &[]), ["tool", "new", "--name=my-app"]);
+        assert_eq!(command("unnamed", &[]), ["tool", "init", "my-app"]);
+    }
+
+    #[test]
+    fn option_args_are_expanded_but_not_what_was_typed() {
+        assert_eq!(
+            command(
+                "unnamed",
+                &["synopsis=A {name} generator", "module={dir}", "license=yes"]
+            ),
+            [
+                "tool",
+                "init",
+                "--synopsis",
+                "A {name} generator",
+                "--module=my_app.{dir}",
+                "--license-file=/work/my-app/LICENSE",
+                // The options' placeholders don't replace the name the command needs
+                "my-app",
+            ]
+        );
+    }
+}
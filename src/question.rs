@@ -0,0 +1,292 @@
+use regex::Regex;
+
+use crate::error::MyError;
+
+/// Something asked after picking a language: one of the language's options, which add arguments
+/// to its command, or one of its template's questions, whose answers become template variables
+pub struct Question {
+    /// What `--option` sets it by, for a template also the variable the answer is stored in
+    pub name: String,
+    /// What it's asked with
+    pub label: String,
+    pub help: Option<String>,
+    pub kind: QuestionKind,
+    /// Only asked if this holds for the answers before it
+    pub when: Option<Condition>,
+}
+
+pub enum QuestionKind {
+    /// A checkbox, adds `args` when checked
+    Bool { default: bool, args: Vec<String> },
+    /// Adds `args` with `{value}` replaced by the text, unless it's empty
+    Text {
+        default: String,
+        /// Has to match the whole answer
+        validate: Option<Regex>,
+        args: Vec<String>,
+    },
+    Int {
+        default: i64,
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    /// One of the choices, adds the `args` of the chosen one
+    Choice {
+        choices: Vec<Choice>,
+        default: usize,
+    },
+    /// Any number of the choices, adds the `args` of every picked one
+    Multi {
+        choices: Vec<Choice>,
+        defaults: Vec<bool>,
+    },
+}
+
+pub struct Choice {
+    pub label: String,
+    pub args: Vec<String>,
+}
+
+/// The answer to a `Question`
+#[derive(Clone, PartialEq, Eq)]
+pub enum Answer {
+    Bool(bool),
+    Text(String),
+    Int(i64),
+    /// Index into the choices
+    Choice(usize),
+    /// Whether each of the choices is picked
+    Multi(Vec<bool>),
+}
+
+/// `when = "name"`, `"!name"`, `"name == value"` or `"name != value"`
+pub struct Condition {
+    /// Index of the question it looks at, always one before the question it belongs to
+    question: usize,
+    test: Test,
+}
+
+enum Test {
+    Set,
+    Unset,
+    Equals(String),
+    NotEquals(String),
+}
+
+impl Question {
+    pub fn default_value(&self) -> Answer {
+        match &self.kind {
+            QuestionKind::Bool { default, .. } => Answer::Bool(*default),
+            QuestionKind::Text { default, .. } => Answer::Text(default.clone()),
+            QuestionKind::Int { default, .. } => Answer::Int(*default),
+            QuestionKind::Choice { default, .. } => Answer::Choice(*default),
+            QuestionKind::Multi { defaults, .. } => Answer::Multi(defaults.clone()),
+        }
+    }
+
+    pub fn choices(&self) -> &[Choice] {
+        match &self.kind {
+            QuestionKind::Choice { choices, .. } | QuestionKind::Multi { choices, .. } => choices,
+            _ => &[],
+        }
+    }
+
+    /// Parses an answer given as text, from the command line or typed in, and checks that it's
+    /// valid: yes or no, a choice by its label, several of them separated by commas, or the text
+    /// or number itself. Errors are phrased to follow "the answer ".
+    pub fn parse_value(&self, value: &str) -> Result<Answer, String> {
+        let find = |label: &str| {
+            self.choices()
+                .iter()
+                .position(|choice| choice.label.eq_ignore_ascii_case(label))
+                .ok_or_else(|| {
+                    let labels: Vec<_> = self
+                        .choices()
+                        .iter()
+                        .map(|choice| choice.label.as_str())
+                        .collect();
+                    format!("`{label}` isn't one of: {}", labels.join(", "))
+                })
+        };
+
+        match &self.kind {
+            QuestionKind::Bool { .. } => match value.to_lowercase().as_str() {
+                "yes" | "true" | "on" => Ok(Answer::Bool(true)),
+                "no" | "false" | "off" => Ok(Answer::Bool(false)),
+                _ => Err("is either yes or no".to_string()),
+            },
+            QuestionKind::Text { validate, .. } => match validate {
+                Some(regex) if !regex.is_match(value) => {
+                    Err(format!("has to match `{}`", pattern(regex)))
+                }
+                _ => Ok(Answer::Text(value.to_string())),
+            },
+            QuestionKind::Int { min, max, .. } => {
+                let number: i64 = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| "has to be a whole number".to_string())?;
+                match (min, max) {
+                    (Some(min), _) if number < *min => Err(format!("can't be less than {min}")),
+                    (_, Some(max)) if number > *max => Err(format!("can't be more than {max}")),
+                    _ => Ok(Answer::Int(number)),
+                }
+            }
+            QuestionKind::Choice { .. } => find(value).map(Answer::Choice),
+            QuestionKind::Multi { choices, .. } => {
+                let mut picked = vec![false; choices.len()];
+                for label in value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|label| !label.is_empty())
+                {
+                    picked[find(label)?] = true;
+                }
+                Ok(Answer::Multi(picked))
+            }
+        }
+    }
+
+    /// `answer` as text, for the summary and as the value of a template variable: `true` or
+    /// `false`, the text or number itself, the label of a choice, or the labels of several
+    /// choices separated by commas
+    pub fn value_string(&self, answer: &Answer) -> String {
+        match answer {
+            Answer::Bool(yes) => yes.to_string(),
+            Answer::Text(text) => text.clone(),
+            Answer::Int(number) => number.to_string(),
+            Answer::Choice(index) => self
+                .choices()
+                .get(*index)
+                .map(|choice| choice.label.clone())
+                .unwrap_or_default(),
+            Answer::Multi(_) => self.picked(answer).join(", "),
+        }
+    }
+
+    /// The labels of the choices picked in `answer`
+    pub fn picked(&self, answer: &Answer) -> Vec<&str> {
+        match answer {
+            Answer::Multi(picked) => self
+                .choices()
+                .iter()
+                .zip(picked)
+                .filter(|(_, &picked)| picked)
+                .map(|(choice, _)| choice.label.as_str())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The arguments `answer` adds to the language's command. `expand` is applied to the
+    /// arguments as they're configured, before `{value}` is replaced, so what the user typed is
+    /// never expanded.
+    pub fn args(&self, answer: &Answer, expand: impl Fn(&str) -> String) -> Vec<String> {
+        let expand_all = |args: &[String]| args.iter().map(|arg| expand(arg)).collect();
+
+        match (&self.kind, answer) {
+            (QuestionKind::Bool { args, .. }, Answer::Bool(true)) => expand_all(args),
+            (QuestionKind::Text { args, .. }, Answer::Text(text)) if !text.is_empty() => args
+                .iter()
+                .map(|arg| expand(arg).replace("{value}", text))
+                .collect(),
+            (QuestionKind::Choice { choices, .. }, &Answer::Choice(index)) => choices
+                .get(index)
+                .map(|choice| expand_all(&choice.args))
+                .unwrap_or_default(),
+            (QuestionKind::Multi { choices, .. }, Answer::Multi(picked)) => choices
+                .iter()
+                .zip(picked)
+                .filter(|(_, &picked)| picked)
+                .flat_map(|(choice, _)| expand_all(&choice.args))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Answer {
+    /// Whether it counts as set for a `when` condition
+    fn is_set(&self) -> bool {
+        match self {
+            Self::Bool(yes) => *yes,
+            Self::Text(text) => !text.is_empty(),
+            Self::Int(number) => *number != 0,
+            Self::Choice(_) => true,
+            Self::Multi(picked) => picked.contains(&true),
+        }
+    }
+}
+
+impl Condition {
+    /// Parses a `when`, which can only look at the questions `before` it
+    pub fn parse(when: &str, before: &[Question]) -> Result<Self, String> {
+        let (name, test) = if let Some((name, value)) = when.split_once("!=") {
+            (name, Test::NotEquals(value.trim().to_string()))
+        } else if let Some((name, value)) = when.split_once("==") {
+            (name, Test::Equals(value.trim().to_string()))
+        } else if let Some(name) = when.trim().strip_prefix('!') {
+            (name, Test::Unset)
+        } else {
+            (when, Test::Set)
+        };
+
+        let name = name.trim();
+        let question = before
+            .iter()
+            .position(|question| question.name == name)
+            .ok_or_else(|| {
+                format!("`when` has to refer to a prompt before this one, `{name}` isn't")
+            })?;
+
+        Ok(Self { question, test })
+    }
+}
+
+/// The answers to start out with: the defaults, with those given as `name=value` in `overrides`
+/// replacing them. Assignments to other names are left alone.
+pub fn answers(questions: &[Question], overrides: &[String]) -> Result<Vec<Answer>, MyError> {
+    let mut answers: Vec<_> = questions.iter().map(Question::default_value).collect();
+
+    for assignment in overrides {
+        let Some((name, value)) = assignment.split_once('=') else {
+            continue;
+        };
+        if let Some(index) = questions.iter().position(|question| question.name == name) {
+            answers[index] = questions[index]
+                .parse_value(value)
+                .map_err(|message| MyError::Usage(format!("`--option {name}`: {message}")))?;
+        }
+    }
+
+    Ok(answers)
+}
+
+/// Whether the question at `index` is asked, given the `answers` to the ones before it
+pub fn is_asked(questions: &[Question], index: usize, answers: &[Answer]) -> bool {
+    let Some(condition) = &questions[index].when else {
+        return true;
+    };
+
+    let value =
+        is_asked(questions, condition.question, answers).then(|| &answers[condition.question]);
+    let question = &questions[condition.question];
+    let equals = |expected: &str| match value {
+        Some(value @ Answer::Multi(_)) => question.picked(value).contains(&expected),
+        Some(value) => question.value_string(value) == expected,
+        None => false,
+    };
+
+    match &condition.test {
+        Test::Set => value.is_some_and(Answer::is_set),
+        Test::Unset => !value.is_some_and(Answer::is_set),
+        Test::Equals(expected) => equals(expected),
+        Test::NotEquals(expected) => !equals(expected),
+    }
+}
+
+/// The regex as it was written, without the anchors added around it
+fn pattern(regex: &Regex) -> &str {
+    let anchored = regex.as_str();
+    &anchored[4..anchored.len() - 2]
+}
@@ -0,0 +1,169 @@
+use crossterm::{
+    cursor, queue,
+    style::{self, StyledContent},
+    terminal::{self, Clear, ClearType},
+};
+use std::io::{Stdout, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// One row of the screen made of differently styled pieces of text
+pub type Line = Vec<StyledContent<String>>;
+
+/// Everything to show on the screen at once
+#[derive(Default)]
+pub struct Frame {
+    pub lines: Vec<Line>,
+    /// Where to put the visible cursor, it's hidden if unset
+    pub cursor: Option<(u16, u16)>,
+}
+
+impl Frame {
+    pub fn push(&mut self, line: Line) {
+        self.lines.push(line);
+    }
+}
+
+/// Draws frames by only redrawing the lines that changed since the previous one, so the screen
+/// doesn't flicker the way clearing it before every frame does
+pub struct Screen {
+    stdout: Stdout,
+    previous: Vec<Line>,
+    width: u16,
+    height: u16,
+    cursor_visible: bool,
+    /// Whether the whole screen has to be cleared before the next frame
+    dirty: bool,
+}
+
+impl Screen {
+    pub fn new(stdout: Stdout) -> Self {
+        // Only matters once something is drawn, by then there's a terminal
+        let (width, height) = terminal::size()
+            .ok()
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .unwrap_or((80, 24));
+
+        Self {
+            stdout,
+            previous: Vec::new(),
+            width,
+            height,
+            cursor_visible: true,
+            dirty: true,
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Call on `Event::Resize`, the next frame is then drawn from scratch
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+        self.previous.clear();
+        self.dirty = true;
+    }
+
+    pub fn draw(&mut self, frame: Frame) -> std::io::Result<()> {
+        let lines: Vec<Line> = frame
+            .lines
+            .into_iter()
+            .take(self.height.into())
+            .map(|line| truncate(line, self.width.into()))
+            .collect();
+
+        if self.dirty {
+            queue!(self.stdout, Clear(ClearType::All))?;
+            self.dirty = false;
+        }
+
+        for row in 0..lines.len().max(self.previous.len()) {
+            let line = lines.get(row);
+            if line == self.previous.get(row) {
+                continue;
+            }
+
+            let row = u16::try_from(row).unwrap_or(u16::MAX);
+            queue!(self.stdout, cursor::MoveTo(0, row))?;
+            for span in line.into_iter().flatten() {
+                queue!(self.stdout, style::PrintStyledContent(span.clone()))?;
+            }
+            queue!(self.stdout, Clear(ClearType::UntilNewLine))?;
+        }
+        self.previous = lines;
+
+        match frame.cursor {
+            Some((column, row)) => {
+                queue!(self.stdout, cursor::MoveTo(column, row))?;
+                if !self.cursor_visible {
+                    queue!(self.stdout, cursor::Show)?;
+                }
+            }
+            None if self.cursor_visible => queue!(self.stdout, cursor::Hide)?,
+            None => {}
+        }
+        self.cursor_visible = frame.cursor.is_some();
+
+        self.stdout.flush()
+    }
+}
+
+/// Breaks `text` into lines of at most `width` columns between words, paragraphs are kept apart
+/// by their line breaks. Words longer than a line are left for `truncate` to cut off.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.width() + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Cuts the line off at `width` columns so it doesn't wrap onto the next row
+fn truncate(line: Line, width: usize) -> Line {
+    let mut remaining = width;
+    let mut truncated = Vec::with_capacity(line.len());
+
+    for span in line {
+        if remaining == 0 {
+            break;
+        }
+
+        let span_width = span.content().width();
+        if span_width <= remaining {
+            remaining -= span_width;
+            truncated.push(span);
+        } else {
+            // A wide character that only half fits is left out entirely
+            let mut content = String::new();
+            for c in span.content().chars() {
+                let char_width = c.width().unwrap_or(0);
+                if char_width > remaining {
+                    break;
+                }
+                remaining -= char_width;
+                content.push(c);
+            }
+            truncated.push(StyledContent::new(*span.style(), content));
+            remaining = 0;
+        }
+    }
+
+    truncated
+}
@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command as Cmd,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::MyError,
+    manifest::{self, Manifest},
+};
+
+/// Files of the built-in templates as `(relative path, contents)` pairs
+const BUILTIN_TEMPLATES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "web",
+        &[
+            ("index.html", include_str!("templates/web/index.html")),
+            ("style.css", include_str!("templates/web/style.css")),
+            ("script.js", include_str!("templates/web/script.js")),
+        ],
+    ),
+    (
+        "cpp",
+        &[
+            ("src/main.cpp", include_str!("templates/cpp/src/main.cpp")),
+            ("Makefile", include_str!("templates/cpp/Makefile")),
+        ],
+    ),
+];
+
+#[derive(Clone)]
+pub enum Template {
+    /// The name and the files of a built-in template
+    Builtin(&'static str, &'static [(&'static str, &'static str)]),
+    Directory(PathBuf),
+}
+
+/// Values substituted for `{{ name }}` placeholders
+pub type Variables = HashMap<String, String>;
+
+impl Template {
+    /// Resolves a template by name, preferring a directory next to the config file over the
+    /// built-in template of the same name
+    pub fn find(name: &str, config_dir: Option<&Path>) -> Option<Self> {
+        if let Some(dir) = config_dir.map(|dir| dir.join(name)) {
+            if dir.is_dir() {
+                return Some(Self::Directory(dir));
+            }
+        }
+
+        BUILTIN_TEMPLATES
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(name, files)| Self::Builtin(name, files))
+    }
+
+    /// Returns all the files of the template as `(relative path, contents)` pairs
+    pub fn files(&self) -> Result<Vec<(PathBuf, Vec<u8>)>, MyError> {
+        match self {
+            Self::Builtin(_, files) => Ok(files
+                .iter()
+                .map(|(path, contents)| (PathBuf::from(path), contents.as_bytes().to_vec()))
+                .collect()),
+            Self::Directory(root) => {
+                let mut files = Vec::new();
+                collect_files(root, Path::new(""), &mut files)?;
+                // The manifest describes the template, it isn't part of the project
+                files.retain(|(path, _)| path != Path::new(manifest::FILE_NAME));
+                Ok(files)
+            }
+        }
+    }
+
+    /// What the template is called in the languages file
+    pub fn name(&self) -> String {
+        match self {
+            Self::Builtin(name, _) => name.to_string(),
+            Self::Directory(root) => root
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// The questions the template asks, only templates in a directory can have them. Built-in
+    /// templates change along with the program, so they have its version.
+    pub fn manifest(&self) -> Result<Manifest, MyError> {
+        match self {
+            Self::Builtin(..) => Ok(Manifest {
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                ..Manifest::default()
+            }),
+            Self::Directory(root) => Manifest::load(root),
+        }
+    }
+
+    /// Renders the paths and contents of every file of the template. Files that aren't text, like
+    /// images, are copied as they are.
+    pub fn render_files(&self, variables: &Variables) -> Result<Vec<(PathBuf, Vec<u8>)>, MyError> {
+        self.files()?
+            .into_iter()
+            .map(|(path, contents)| {
+                let template_error = |message| MyError::Template {
+                    file: path.display().to_string(),
+                    message,
+                };
+
+                let rendered_path =
+                    render(&path.to_string_lossy(), variables).map_err(template_error)?;
+                let contents = match String::from_utf8(contents) {
+                    Ok(text) => render(&text, variables)
+                        .map_err(template_error)?
+                        .into_bytes(),
+                    Err(binary) => binary.into_bytes(),
+                };
+                Ok((PathBuf::from(rendered_path), contents))
+            })
+            .collect()
+    }
+}
+
+fn collect_files(
+    root: &Path,
+    relative: &Path,
+    files: &mut Vec<(PathBuf, Vec<u8>)>,
+) -> Result<(), MyError> {
+    let dir = root.join(relative);
+    let mut entries = fs::read_dir(&dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| read_error(&dir, e))?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = relative.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(|e| read_error(&entry.path(), e))?;
+        if file_type.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let contents = fs::read(entry.path()).map_err(|e| read_error(&entry.path(), e))?;
+            files.push((path, contents));
+        }
+    }
+
+    Ok(())
+}
+
+/// Says which file of a template couldn't be read
+fn read_error(path: &Path, e: io::Error) -> MyError {
+    MyError::Io(io::Error::new(
+        e.kind(),
+        format!("couldn't read {}: {e}", path.display()),
+    ))
+}
+
+/// Replaces every `{{ name }}` in `source` with the value of the variable. `\{{` is kept as a
+/// literal `{{`, and `\\{{` is a backslash followed by the value, e.g. `C:\\{{ project_name }}`.
+pub fn render(source: &str, variables: &Variables) -> Result<String, String> {
+    let mut output = String::with_capacity(source.len());
+    let mut rest = source;
+
+    while let Some(start) = rest.find("{{") {
+        let before = &rest[..start];
+        if before.ends_with("\\\\") {
+            output.push_str(&before[..start - 1]);
+        } else if before.ends_with('\\') {
+            output.push_str(&before[..start - 1]);
+            output.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        } else {
+            output.push_str(before);
+        }
+
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| "unclosed `{{`".to_string())?;
+
+        let name = after[..end].trim();
+        let value = variables
+            .get(name)
+            .ok_or_else(|| format!("unknown variable `{name}`"))?;
+        output.push_str(value);
+
+        rest = &after[end + 2..];
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// The variables every template has access to
+pub fn variables(project_name: &str) -> Variables {
+    Variables::from([
+        ("project_name".to_string(), project_name.to_string()),
+        ("crate_name".to_string(), crate_name(project_name)),
+        ("author".to_string(), author()),
+        ("year".to_string(), current_year().to_string()),
+    ])
+}
+
+/// Turns the project name into an identifier, e.g. `My-Project` into `my_project`
+fn crate_name(project_name: &str) -> String {
+    project_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn author() -> String {
+    let git_name = Cmd::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    git_name
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default()
+}
+
+fn current_year() -> i64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86_400) as i64;
+
+    // Howard Hinnant's `civil_from_days`, only the year part of it
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    yoe + era * 400 + i64::from(month <= 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{crate_name, render, Template, Variables};
+    use crate::test_dir::TestDir;
+
+    fn variables() -> Variables {
+        Variables::from([
+            ("project_name".to_string(), "My-Project".to_string()),
+            ("crate_name".to_string(), "my_project".to_string()),
+        ])
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        assert_eq!(
+            render("{{project_name}} is {{ crate_name }}.", &variables()).as_deref(),
+            Ok("My-Project is my_project.")
+        );
+        assert_eq!(
+            render("no {braces} } here", &variables()).as_deref(),
+            Ok("no {braces} } here")
+        );
+    }
+
+    #[test]
+    fn unknown_and_unclosed() {
+        assert_eq!(
+            render("{{ nope }}", &variables()),
+            Err("unknown variable `nope`".to_string())
+        );
+        assert_eq!(
+            render("{{ crate_name }} and {{ crate_name", &variables()),
+            Err("unclosed `{{`".to_string())
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        // A Vue component keeps its own placeholders
+        assert_eq!(
+            render(r"<p>\{{ message }}</p> {{ crate_name }}", &variables()).as_deref(),
+            Ok("<p>{{ message }}</p> my_project")
+        );
+        // A Windows path ends in a backslash right before the value
+        assert_eq!(
+            render(r"cd C:\\{{ project_name }}", &variables()).as_deref(),
+            Ok(r"cd C:\My-Project")
+        );
+        // Backslashes anywhere else are left alone
+        assert_eq!(
+            render(r"a\b \\ {{ crate_name }}", &variables()).as_deref(),
+            Ok(r"a\b \\ my_project")
+        );
+    }
+
+    #[test]
+    fn binary_files_are_copied_as_they_are() {
+        let dir = TestDir::new();
+        let image = [
+            0x89, b'P', b'N', b'G', 0xff, b'{', b'{', b' ', b'x', b' ', b'}', b'}',
+        ];
+        dir.write("{{ crate_name }}.png", image);
+        dir.write("README", "# {{ project_name }}");
+
+        let files = Template::Directory(dir.path().to_path_buf())
+            .render_files(&variables())
+            .unwrap();
+        assert!(
+            files
+                == [
+                    (PathBuf::from("README"), b"# My-Project".to_vec()),
+                    (PathBuf::from("my_project.png"), image.to_vec()),
+                ]
+        );
+    }
+
+    #[test]
+    fn crate_names() {
+        assert_eq!(crate_name("My-Project"), "my_project");
+        assert_eq!(crate_name("app2 go.v"), "app2_go_v");
+        assert_eq!(crate_name("café"), "caf_");
+    }
+}
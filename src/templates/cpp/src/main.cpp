@@ -0,0 +1,9 @@
+// {{ project_name }}
+// Copyright (c) {{ year }} {{ author }}
+
+#include <iostream>
+
+int main() {
+    std::cout << "Hello from {{ project_name }}!" << std::endl;
+    return 0;
+}
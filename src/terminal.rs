@@ -0,0 +1,94 @@
+use crossterm::{
+    cursor,
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    execute,
+    terminal::{self, disable_raw_mode, enable_raw_mode},
+};
+use signal_hook::{
+    consts::{SIGHUP, SIGINT, SIGTERM},
+    iterator::Signals,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the terminal is currently in raw mode because of a guard
+static RAW_MODE: AtomicBool = AtomicBool::new(false);
+/// Whether the alternate screen is currently shown because of a guard
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+/// Whether a signal should end the process, turned off while something else handles them
+static EXIT_ON_SIGNAL: AtomicBool = AtomicBool::new(true);
+
+/// Puts the terminal back to normal when dropped, no matter how the scope is left
+pub struct TerminalGuard(());
+
+impl TerminalGuard {
+    /// Switches to the alternate screen in raw mode with bracketed paste, for the TUI
+    pub fn enter() -> std::io::Result<Self> {
+        let guard = Self::raw()?;
+        execute!(
+            std::io::stdout(),
+            terminal::EnterAlternateScreen,
+            EnableBracketedPaste
+        )?;
+        ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+
+        Ok(guard)
+    }
+
+    /// Only switches to raw mode, for reading single key presses
+    pub fn raw() -> std::io::Result<Self> {
+        enable_raw_mode()?;
+        RAW_MODE.store(true, Ordering::SeqCst);
+
+        Ok(Self(()))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leaves the alternate screen, shows the cursor and disables raw mode, as far as any of it was
+/// done by a guard. Errors are ignored since there's nothing better to do at this point.
+pub fn restore() {
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = execute!(
+            std::io::stdout(),
+            DisableBracketedPaste,
+            terminal::LeaveAlternateScreen,
+            cursor::Show
+        );
+    }
+
+    if RAW_MODE.swap(false, Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Makes panics and termination signals restore the terminal before the process ends
+pub fn install_handlers() -> std::io::Result<()> {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        default_hook(info);
+    }));
+
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if EXIT_ON_SIGNAL.load(Ordering::SeqCst) {
+                restore();
+                // The usual exit code of a process killed by a signal
+                std::process::exit(128 + signal);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Stops signals from ending the process, for when they're handled some other way
+pub fn keep_running_on_signals() {
+    EXIT_ON_SIGNAL.store(false, Ordering::SeqCst);
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::HashMap,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command as Cmd, Stdio},
+};
+
+use crate::languages::{Language, Languages};
+
+/// Where the programs the languages need were found, looked up once at startup
+pub struct Toolchains {
+    found: HashMap<String, Option<PathBuf>>,
+}
+
+impl Toolchains {
+    pub fn detect(languages: &Languages) -> Self {
+        let mut found = HashMap::new();
+        for language in languages.iter() {
+            for program in language.required_programs() {
+                found
+                    .entry(program.to_string())
+                    .or_insert_with(|| find_program(program));
+            }
+        }
+
+        Self { found }
+    }
+
+    pub fn path(&self, program: &str) -> Option<&Path> {
+        self.found.get(program)?.as_deref()
+    }
+
+    /// The first program `language` needs that isn't installed
+    pub fn missing<'a>(&self, language: &'a Language) -> Option<&'a str> {
+        language
+            .required_programs()
+            .into_iter()
+            .find(|program| self.path(program).is_none())
+    }
+}
+
+/// Finds `program` like a shell would, on PATH unless it's a path itself
+pub fn find_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return is_executable(&path).then_some(path);
+    }
+
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        // An empty entry means the current directory
+        .map(|dir| {
+            if dir.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                dir
+            }
+        })
+        .map(|dir| dir.join(program))
+        .find(|path| is_executable(path))
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+/// The first line `program --version` prints, if it prints anything
+pub fn version(path: &Path) -> Option<String> {
+    let output = Cmd::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Prints whether the programs of every language are installed, for the `doctor` subcommand
+pub fn doctor(languages: &Languages, toolchains: &Toolchains) {
+    for language in languages.iter() {
+        println!("{language} ({})", language.name);
+
+        let programs = language.required_programs();
+        if programs.is_empty() {
+            println!("  needs nothing");
+        }
+
+        let width = programs
+            .iter()
+            .map(|program| program.len())
+            .max()
+            .unwrap_or(0);
+        for program in programs {
+            match toolchains.path(program) {
+                Some(path) => println!(
+                    "  {program:width$}  {}  {}",
+                    path.display(),
+                    version(path).unwrap_or_else(|| "unknown version".to_string())
+                ),
+                None => println!("  {program:width$}  not found"),
+            }
+        }
+    }
+}
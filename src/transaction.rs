@@ -0,0 +1,287 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use crate::terminal;
+
+/// Keeps track of the paths created while creating a project so they can be removed again if
+/// it fails. Paths that already existed are never recorded, so they can't be removed either.
+/// Existing files that are replaced are moved aside first and put back if it fails.
+#[derive(Default)]
+pub struct Transaction {
+    created: Vec<PathBuf>,
+    /// Replaced paths along with where they were moved to
+    replaced: Vec<(PathBuf, PathBuf)>,
+    /// Directories only needed until the project is created, like the one holding the replaced
+    /// paths
+    temporary: Vec<PathBuf>,
+}
+
+/// Something `Transaction::rollback` undid
+pub enum Undone {
+    Removed(PathBuf),
+    Restored(PathBuf),
+}
+
+impl Transaction {
+    /// Records `path` if it doesn't exist yet, call before creating it
+    pub fn record(&mut self, path: &Path) {
+        let path = &normalize(path);
+        if !path.exists() && !self.created.iter().any(|created| created == path) {
+            self.created.push(path.to_path_buf());
+        }
+    }
+
+    /// Records every directory that `fs::create_dir_all(dir)` would create
+    pub fn record_dir_all(&mut self, dir: &Path) {
+        let dir = &normalize(dir);
+        let missing: Vec<_> = dir.ancestors().take_while(|dir| !dir.exists()).collect();
+        for dir in missing.into_iter().rev() {
+            self.record(dir);
+        }
+    }
+
+    /// Whether `path` is one the transaction created or replaced, or inside one of them. Any other
+    /// path that exists was there before.
+    pub fn is_recorded(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.created
+            .iter()
+            .chain(self.replaced.iter().map(|(replaced, _)| replaced))
+            .any(|recorded| path.starts_with(recorded))
+    }
+
+    /// Moves the existing `path` to `backup` so something else can take its place, it's moved
+    /// back on rollback
+    pub fn replace(&mut self, path: &Path, backup: &Path) -> std::io::Result<()> {
+        if let Some(parent) = backup.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(path, backup)?;
+        self.replaced.push((normalize(path), backup.to_path_buf()));
+        Ok(())
+    }
+
+    /// Records `dir` to be removed once the project is created, and on rollback
+    pub fn remove_when_done(&mut self, dir: &Path) {
+        self.temporary.push(normalize(dir));
+    }
+
+    /// Removes the temporary directories, the replaced paths in them are gone for good then
+    pub fn commit(self) {
+        for dir in self.temporary {
+            if let Err(e) = fs::remove_dir_all(&dir) {
+                eprintln!("Couldn't remove {}: {e}", dir.display());
+            }
+        }
+    }
+
+    /// Puts the replaced paths back and removes everything that was recorded, returning what was
+    /// undone. Paths inside a removed directory go with it and aren't listed separately.
+    pub fn rollback(self) -> Vec<Undone> {
+        let mut undone = Vec::new();
+
+        // First, as the backups are kept inside directories that are removed below
+        for (path, backup) in self.replaced.into_iter().rev() {
+            let result = remove(&path).and_then(|()| fs::rename(&backup, &path));
+            match result {
+                Ok(()) => undone.push(Undone::Restored(path)),
+                Err(e) => eprintln!(
+                    "Couldn't restore {} from {}: {e}",
+                    path.display(),
+                    backup.display()
+                ),
+            }
+        }
+
+        for path in self.created {
+            if fs::symlink_metadata(&path).is_err() {
+                continue;
+            }
+
+            match remove(&path) {
+                Ok(()) => undone.push(Undone::Removed(path)),
+                Err(e) => eprintln!("Couldn't remove {}: {e}", path.display()),
+            }
+        }
+
+        for dir in self.temporary {
+            if let Err(e) = remove(&dir) {
+                eprintln!("Couldn't remove {}: {e}", dir.display());
+            }
+        }
+
+        undone
+    }
+}
+
+/// Removes a file or a directory with everything in it, nothing there is fine too
+fn remove(path: &Path) -> std::io::Result<()> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+
+    if metadata.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Resolves `.` and `..` without touching the file system, so that a recorded path stays valid
+/// even when a directory it went through is removed
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            // The root is its own parent
+            Component::ParentDir if normalized.has_root() => {}
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Makes Ctrl-C, SIGTERM and SIGHUP set the returned flag instead of killing the process, so
+/// that a rollback can still happen. Child processes still get the signal and usually exit.
+pub fn catch_interrupts() -> std::io::Result<Arc<AtomicBool>> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+
+    for signal in [
+        signal_hook::consts::SIGINT,
+        signal_hook::consts::SIGTERM,
+        signal_hook::consts::SIGHUP,
+    ] {
+        signal_hook::flag::register(signal, Arc::clone(&interrupted))?;
+    }
+    terminal::keep_running_on_signals();
+
+    Ok(interrupted)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::{normalize, Transaction, Undone};
+    use crate::test_dir::TestDir;
+
+    /// What was undone, `+` for restored and `-` for removed paths relative to `root`
+    fn undone(undone: Vec<Undone>, root: &Path) -> Vec<String> {
+        undone
+            .into_iter()
+            .map(|undone| {
+                let (sign, path) = match undone {
+                    Undone::Restored(path) => ('+', path),
+                    Undone::Removed(path) => ('-', path),
+                };
+                format!("{sign}{}", path.strip_prefix(root).unwrap().display())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rollback_removes_only_what_was_recorded() {
+        let dir = TestDir::new();
+        let root = dir.path();
+        dir.write("existing/keep.txt", "mine");
+
+        let mut transaction = Transaction::default();
+        transaction.record_dir_all(&root.join("new/nested"));
+        fs::create_dir_all(root.join("new/nested")).unwrap();
+        dir.write("new/nested/file.txt", "generated");
+        // Already there, so it's not recorded
+        transaction.record(&root.join("existing"));
+        transaction.record(&root.join("existing/added.txt"));
+        dir.write("existing/added.txt", "generated");
+        // Created by something else
+        dir.write("unrelated.txt", "someone else's");
+
+        assert_eq!(
+            undone(transaction.rollback(), root),
+            ["-new", "-existing/added.txt"]
+        );
+        assert!(!root.join("new").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("existing/keep.txt")).unwrap(),
+            "mine"
+        );
+        assert!(root.join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn rollback_restores_replaced_files() {
+        let dir = TestDir::new();
+        let root = dir.path();
+        let page = dir.write("project/index.html", "mine");
+        dir.write("project/style.css", "mine too");
+
+        let mut transaction = Transaction::default();
+        transaction.remove_when_done(&root.join("backup"));
+        transaction
+            .replace(&page, &root.join("backup/index.html"))
+            .unwrap();
+        fs::write(&page, "generated").unwrap();
+
+        assert_eq!(
+            undone(transaction.rollback(), root),
+            ["+project/index.html"]
+        );
+        assert_eq!(fs::read_to_string(&page).unwrap(), "mine");
+        assert_eq!(
+            fs::read_to_string(root.join("project/style.css")).unwrap(),
+            "mine too"
+        );
+        assert!(!root.join("backup").exists());
+    }
+
+    #[test]
+    fn commit_keeps_the_replacements() {
+        let dir = TestDir::new();
+        let root = dir.path();
+        let page = dir.write("index.html", "mine");
+
+        let mut transaction = Transaction::default();
+        transaction.remove_when_done(&root.join("backup"));
+        transaction
+            .replace(&page, &root.join("backup/index.html"))
+            .unwrap();
+        fs::write(&page, "generated").unwrap();
+        transaction.commit();
+
+        assert_eq!(fs::read_to_string(&page).unwrap(), "generated");
+        assert!(!root.join("backup").exists());
+    }
+
+    #[test]
+    fn paths_are_recorded_normalized() {
+        let dir = TestDir::new();
+        let root = dir.path();
+        fs::create_dir(root.join("existing")).unwrap();
+
+        // Going through `existing` again mustn't record it, nor the same path twice
+        let mut transaction = Transaction::default();
+        transaction.record(&root.join("existing/../new"));
+        transaction.record(&root.join("./new"));
+        transaction.record(&root.join("new/../existing"));
+        fs::create_dir(root.join("new")).unwrap();
+
+        assert_eq!(undone(transaction.rollback(), root), ["-new"]);
+        assert!(root.join("existing").exists());
+    }
+
+    #[test]
+    fn normalizes_dots() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")), Path::new("/a/c"));
+        assert_eq!(normalize(Path::new("a/b/../../c")), Path::new("c"));
+        assert_eq!(normalize(Path::new("../a/./b")), Path::new("../a/b"));
+        // Nothing to go up to from the root
+        assert_eq!(normalize(Path::new("/../a")), Path::new("/a"));
+    }
+}
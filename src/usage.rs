@@ -0,0 +1,92 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How the languages are sorted in the menu
+#[derive(Clone, Copy, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    /// The order of the languages file
+    #[default]
+    Configured,
+    /// By display name
+    Alphabetical,
+    /// Most recently used first
+    Recent,
+    /// Most frequently used first
+    Frequent,
+}
+
+/// Which languages were used how often and when, remembered between runs for sorting the menu
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Usage {
+    languages: BTreeMap<String, LanguageUsage>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct LanguageUsage {
+    count: u64,
+    /// Seconds since the Unix epoch
+    last_used: u64,
+}
+
+/// Path of the usage state, `$XDG_STATE_HOME/project-bootstrapper/usage.toml`
+pub fn state_path() -> Option<PathBuf> {
+    let state_home = match std::env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?)
+            .join(".local")
+            .join("state"),
+    };
+
+    Some(state_home.join("project-bootstrapper").join("usage.toml"))
+}
+
+impl Usage {
+    /// Loads the saved usage. It's only used for sorting, so a missing or broken file just
+    /// counts as nothing having been used yet.
+    pub fn load() -> Self {
+        state_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let Some(path) = state_path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let text = toml::to_string(self).map_err(std::io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    pub fn record(&mut self, language: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+
+        let usage = self.languages.entry(language.to_string()).or_default();
+        usage.count += 1;
+        usage.last_used = now;
+    }
+
+    pub fn count(&self, language: &str) -> u64 {
+        self.languages.get(language).map_or(0, |usage| usage.count)
+    }
+
+    pub fn last_used(&self, language: &str) -> u64 {
+        self.languages
+            .get(language)
+            .map_or(0, |usage| usage.last_used)
+    }
+}
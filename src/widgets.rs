@@ -0,0 +1,104 @@
+//! Reusable pieces of the TUI, each handling its own input and rendering into a `Frame`. Every
+//! page of the wizard is built from them, so they all share the same keys and look.
+
+pub mod checklist;
+pub mod confirm;
+pub mod form;
+pub mod select;
+pub mod text_input;
+
+pub use checklist::Checklist;
+pub use confirm::Confirm;
+pub use form::{heading, Field, Form};
+pub use select::{List, Select};
+pub use text_input::TextInput;
+
+use crossterm::{
+    event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    style::{self, ContentStyle, StyledContent, Stylize},
+};
+
+use crate::{
+    error::MyError,
+    screen::{self, Line, Screen},
+};
+
+/// How the user left a page of the wizard
+pub enum Outcome<T> {
+    /// Answered it and wants to continue
+    Next(T),
+    /// Pressed Esc to go back to the previous page
+    Back,
+}
+
+impl<T> Outcome<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        match self {
+            Self::Next(value) => Outcome::Next(f(value)),
+            Self::Back => Outcome::Back,
+        }
+    }
+}
+
+/// Something the user did that a page has to react to
+pub enum Input {
+    Key(KeyEvent),
+    Paste(String),
+}
+
+/// Waits for the next key press or paste. What every page does the same way is handled here:
+/// Ctrl-C ends the program and a resize only needs a redraw, which `None` asks for.
+pub fn read_input(screen: &mut Screen) -> Result<Option<Input>, MyError> {
+    match crossterm::event::read()? {
+        Event::Key(key)
+            if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL =>
+        {
+            Err(MyError::GracefulShutdown)
+        }
+        Event::Key(key) if key.kind != KeyEventKind::Release => Ok(Some(Input::Key(key))),
+        Event::Paste(text) => Ok(Some(Input::Paste(text))),
+        Event::Resize(width, height) => {
+            screen.resize(width, height);
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// The style of an entry in a list, which stands out if the keys act on it
+pub fn entry_style(focused: bool) -> ContentStyle {
+    if focused {
+        ContentStyle::new().yellow()
+    } else {
+        ContentStyle::new().magenta()
+    }
+}
+
+/// An entry in a list, marked and highlighted if the keys act on it
+pub fn entry(text: &str, focused: bool) -> StyledContent<String> {
+    let marker = if focused { "> " } else { "  " };
+    StyledContent::new(entry_style(focused), format!("{marker}{text}"))
+}
+
+/// A question at the top of a page
+pub fn title(text: impl Into<String>) -> Line {
+    vec![style::style(text.into())]
+}
+
+/// Which keys do what on the page, shown at its bottom
+pub fn hint(text: &str) -> Line {
+    vec![text.to_string().yellow()]
+}
+
+/// A longer explanation of what's focused, wrapped to `width` and shown below it
+pub fn help(text: &str, width: u16) -> Vec<Line> {
+    screen::wrap(text.trim_end(), width.into())
+        .into_iter()
+        .map(|line| vec![line.dark_grey()])
+        .collect()
+}
+
+/// Why what was entered can't be used
+pub fn error(text: impl Into<String>) -> Line {
+    vec![text.into().red()]
+}
@@ -0,0 +1,55 @@
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::Select;
+use crate::screen::Line;
+
+/// A list of entries that can each be checked, for picking any number of them
+pub struct Checklist {
+    entries: Vec<String>,
+    checked: Vec<bool>,
+    select: Select,
+}
+
+impl Checklist {
+    /// `checked` says which entries start out checked, missing ones aren't
+    pub fn new(entries: Vec<String>, mut checked: Vec<bool>) -> Self {
+        checked.resize(entries.len(), false);
+        Self {
+            select: Select::new(entries.len()),
+            entries,
+            checked,
+        }
+    }
+
+    pub fn checked(&self) -> &[bool] {
+        &self.checked
+    }
+
+    /// Space checks or unchecks the selected entry, the rest moves the selection like in a
+    /// `Select`. Returns whether the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(' ') => {
+                if let Some(selected) = self.select.selected() {
+                    self.checked[selected] = !self.checked[selected];
+                }
+                true
+            }
+            _ => self.select.handle_key(key),
+        }
+    }
+
+    /// The entries that fit into `height` rows, the selection is only shown if `focused`
+    pub fn render(&mut self, height: usize, focused: bool) -> Vec<Line> {
+        self.select
+            .visible(height)
+            .map(|index| {
+                let mark = if self.checked[index] { "[x]" } else { "[ ]" };
+                vec![super::entry(
+                    &format!("{mark} {}", self.entries[index]),
+                    focused && Some(index) == self.select.selected(),
+                )]
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,55 @@
+use crossterm::{
+    event::{KeyCode, KeyEvent},
+    style::Stylize,
+};
+
+use crate::screen::Line;
+
+/// A yes or no question
+pub struct Confirm {
+    question: String,
+    yes: bool,
+}
+
+impl Confirm {
+    pub fn new(question: impl Into<String>, yes: bool) -> Self {
+        Self {
+            question: question.into(),
+            yes,
+        }
+    }
+
+    pub fn yes(&self) -> bool {
+        self.yes
+    }
+
+    /// `y` and `n` answer directly, Left, Right and Space switch between the answers. Returns
+    /// whether the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('y' | 'Y') => self.yes = true,
+            KeyCode::Char('n' | 'N') => self.yes = false,
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') => self.yes = !self.yes,
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn render(&self) -> Line {
+        let answer = |text: &str, chosen: bool| {
+            if chosen {
+                format!(" {text} ").black().on_yellow()
+            } else {
+                format!(" {text} ").magenta()
+            }
+        };
+
+        vec![
+            format!("{} ", self.question).bold(),
+            answer("Yes", self.yes),
+            " ".to_string().stylize(),
+            answer("No", !self.yes),
+        ]
+    }
+}
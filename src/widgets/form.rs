@@ -0,0 +1,168 @@
+use crossterm::{
+    event::{KeyCode, KeyEvent},
+    style::{StyledContent, Stylize},
+};
+
+use super::{entry_style, Checklist, TextInput};
+use crate::screen::Line;
+
+/// One part of a `Form`
+pub enum Field {
+    Text(TextInput),
+    Checkbox {
+        label: String,
+        checked: bool,
+    },
+    /// One of a few choices, all shown next to each other
+    Choice {
+        label: String,
+        choices: Vec<String>,
+        selected: usize,
+    },
+    /// Any number of choices, shown below the label
+    Checklist {
+        label: String,
+        list: Checklist,
+    },
+}
+
+/// Several fields below each other, one of them focused and getting the keys
+pub struct Form {
+    fields: Vec<Field>,
+    focused: usize,
+}
+
+impl Form {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Self { fields, focused: 0 }
+    }
+
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// Tab and Shift-Tab move the focus to the next or previous field, Up and Down do too unless
+    /// the focused field uses them. The focused field gets every other key: Space toggles a
+    /// checkbox, Space, Left and Right go through choices and text fields are edited as usual.
+    /// Returns whether the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let len = self.fields.len();
+        if len == 0 {
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Tab => self.focused = (self.focused + 1) % len,
+            KeyCode::BackTab => self.focused = self.focused.checked_sub(1).unwrap_or(len - 1),
+            _ if self.handle_field_key(key) => {}
+            KeyCode::Up => self.focused = self.focused.saturating_sub(1),
+            KeyCode::Down => self.focused = (self.focused + 1).min(len - 1),
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn handle_field_key(&mut self, key: KeyEvent) -> bool {
+        match &mut self.fields[self.focused] {
+            Field::Text(input) => input.handle_key(key),
+            Field::Checkbox { checked, .. } => {
+                let toggle = key.code == KeyCode::Char(' ');
+                if toggle {
+                    *checked = !*checked;
+                }
+                toggle
+            }
+            Field::Choice {
+                choices, selected, ..
+            } => match key.code {
+                KeyCode::Char(' ') | KeyCode::Right => {
+                    *selected = (*selected + 1) % choices.len();
+                    true
+                }
+                KeyCode::Left => {
+                    *selected = selected.checked_sub(1).unwrap_or(choices.len() - 1);
+                    true
+                }
+                _ => false,
+            },
+            Field::Checklist { list, .. } => list.handle_key(key),
+        }
+    }
+
+    /// Pasted text goes into the focused field if it's a text field
+    pub fn paste(&mut self, text: &str) {
+        if let Some(Field::Text(input)) = self.fields.get_mut(self.focused) {
+            input.paste(text);
+        }
+    }
+
+    /// Renders every field into `width` columns. Also returns where the cursor should be shown
+    /// relative to the first line, if the focused field is a text field.
+    pub fn render(&mut self, width: u16) -> (Vec<Line>, Option<(u16, u16)>) {
+        let mut lines: Vec<Line> = Vec::new();
+        let mut cursor = None;
+
+        for (index, field) in self.fields.iter_mut().enumerate() {
+            let focused = index == self.focused;
+            let style = entry_style(focused);
+            let marker = StyledContent::new(style, if focused { "> " } else { "  " }.to_string());
+
+            match field {
+                Field::Text(input) => {
+                    let (mut line, column) = input.render(width.saturating_sub(2));
+                    if focused {
+                        let row = u16::try_from(lines.len()).unwrap_or(u16::MAX);
+                        cursor = Some((column + 2, row));
+                    }
+                    line.insert(0, marker);
+                    lines.push(line);
+                }
+                Field::Checkbox { label, checked } => {
+                    let mark = if *checked { "[x]" } else { "[ ]" };
+                    lines.push(vec![
+                        marker,
+                        StyledContent::new(style, format!("{mark} {label}")),
+                    ]);
+                }
+                Field::Choice {
+                    label,
+                    choices,
+                    selected,
+                } => {
+                    let mut line = vec![marker, StyledContent::new(style, heading(label))];
+                    for (choice_index, choice) in choices.iter().enumerate() {
+                        line.push(if choice_index == *selected {
+                            StyledContent::new(style, format!("  (•) {choice}")).bold()
+                        } else {
+                            StyledContent::new(style, format!("  ( ) {choice}"))
+                        });
+                    }
+                    lines.push(line);
+                }
+                Field::Checklist { label, list } => {
+                    lines.push(vec![marker, StyledContent::new(style, heading(label))]);
+                    for mut line in list.render(u16::MAX.into(), focused) {
+                        line.insert(0, "  ".to_string().stylize());
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+
+        (lines, cursor)
+    }
+}
+
+/// The label of a field with its choices below or after it, a question keeps its question mark
+pub fn heading(label: &str) -> String {
+    if label.ends_with(['?', ':']) {
+        label.to_string()
+    } else {
+        format!("{label}:")
+    }
+}
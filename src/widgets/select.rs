@@ -0,0 +1,212 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ops::Range;
+
+use crate::screen::Line;
+
+/// Which one of a list of entries is selected, and which part of the list is scrolled into view.
+/// Drawing the entries is left to the caller, `List` does it for plain text entries.
+pub struct Select {
+    len: usize,
+    selected: usize,
+    /// Index of the first visible entry
+    offset: usize,
+    /// How many entries fit on the screen, as of the last `visible` call
+    page: usize,
+}
+
+impl Select {
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            selected: 0,
+            offset: 0,
+            page: 1,
+        }
+    }
+
+    /// Selects the entry at `index` if there is one
+    pub fn select(&mut self, index: usize) {
+        if index < self.len {
+            self.selected = index;
+        }
+    }
+
+    /// Index of the selected entry, `None` only if there are no entries
+    pub fn selected(&self) -> Option<usize> {
+        (self.selected < self.len).then_some(self.selected)
+    }
+
+    /// Moves the selection according to the key, returns whether the key was used. Up and Down
+    /// wrap around at the ends, paging stops at them.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.len == 0 {
+            return false;
+        }
+        let last = self.len - 1;
+
+        let vim = |c| key.code == KeyCode::Char(c) && key.modifiers.is_empty();
+        let previous = self.selected.checked_sub(1).unwrap_or(last);
+        let next = if self.selected == last {
+            0
+        } else {
+            self.selected + 1
+        };
+
+        self.selected = match key.code {
+            KeyCode::Up => previous,
+            KeyCode::Down => next,
+            KeyCode::Char('p') if key.modifiers == KeyModifiers::CONTROL => previous,
+            KeyCode::Char('n') if key.modifiers == KeyModifiers::CONTROL => next,
+            _ if vim('k') => previous,
+            _ if vim('j') => next,
+            KeyCode::PageUp => self.selected.saturating_sub(self.page),
+            KeyCode::PageDown => (self.selected + self.page).min(last),
+            KeyCode::Home => 0,
+            KeyCode::End => last,
+            _ => return false,
+        };
+
+        true
+    }
+
+    /// The entries to draw into `height` rows, scrolled just enough to show the selected one
+    pub fn visible(&mut self, height: usize) -> Range<usize> {
+        let height = height.max(1);
+        self.page = height;
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + height {
+            self.offset = self.selected + 1 - height;
+        }
+        // The list may have gotten taller since it was last scrolled
+        self.offset = self.offset.min(self.len.saturating_sub(height));
+
+        self.offset..(self.offset + height).min(self.len)
+    }
+}
+
+/// A single select of plain text entries
+pub struct List {
+    entries: Vec<String>,
+    select: Select,
+}
+
+impl List {
+    pub fn new(entries: Vec<String>) -> Self {
+        Self {
+            select: Select::new(entries.len()),
+            entries,
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.select.selected()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.select.handle_key(key)
+    }
+
+    /// The entries that fit into `height` rows
+    pub fn render(&mut self, height: usize) -> Vec<Line> {
+        self.select
+            .visible(height)
+            .map(|index| {
+                vec![super::entry(
+                    &self.entries[index],
+                    Some(index) == self.select.selected(),
+                )]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    use super::Select;
+
+    fn press(select: &mut Select, code: KeyCode) -> bool {
+        select.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn up_and_down_wrap_around() {
+        let mut select = Select::new(3);
+
+        assert!(press(&mut select, KeyCode::Up));
+        assert_eq!(select.selected(), Some(2));
+        assert!(press(&mut select, KeyCode::Down));
+        assert_eq!(select.selected(), Some(0));
+        press(&mut select, KeyCode::Char('j'));
+        assert_eq!(select.selected(), Some(1));
+        select.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        assert_eq!(select.selected(), Some(0));
+        // Typed with Shift it's a capital letter, not a movement
+        assert!(!select.handle_key(KeyEvent::new(KeyCode::Char('k'), KeyModifiers::SHIFT)));
+        assert_eq!(select.selected(), Some(0));
+    }
+
+    #[test]
+    fn paging_stops_at_the_ends() {
+        let mut select = Select::new(10);
+        select.visible(4);
+
+        press(&mut select, KeyCode::PageDown);
+        assert_eq!(select.selected(), Some(4));
+        press(&mut select, KeyCode::PageDown);
+        press(&mut select, KeyCode::PageDown);
+        assert_eq!(select.selected(), Some(9));
+        press(&mut select, KeyCode::PageUp);
+        assert_eq!(select.selected(), Some(5));
+        press(&mut select, KeyCode::PageUp);
+        press(&mut select, KeyCode::PageUp);
+        assert_eq!(select.selected(), Some(0));
+    }
+
+    #[test]
+    fn home_and_end() {
+        let mut select = Select::new(5);
+
+        press(&mut select, KeyCode::End);
+        assert_eq!(select.selected(), Some(4));
+        press(&mut select, KeyCode::Home);
+        assert_eq!(select.selected(), Some(0));
+    }
+
+    #[test]
+    fn scrolls_to_the_selection() {
+        let mut select = Select::new(10);
+        assert_eq!(select.visible(3), 0..3);
+
+        select.select(5);
+        assert_eq!(select.visible(3), 3..6);
+        // Moving within the visible entries doesn't scroll
+        select.select(4);
+        assert_eq!(select.visible(3), 3..6);
+        select.select(1);
+        assert_eq!(select.visible(3), 1..4);
+
+        // Getting taller shows as many entries as fit, without leaving empty rows
+        press(&mut select, KeyCode::End);
+        assert_eq!(select.visible(3), 7..10);
+        assert_eq!(select.visible(20), 0..10);
+        // Not even a single row still shows the selection
+        assert_eq!(select.visible(0), 9..10);
+    }
+
+    #[test]
+    fn empty_list() {
+        let mut select = Select::new(0);
+
+        assert_eq!(select.selected(), None);
+        for code in [KeyCode::Up, KeyCode::Down, KeyCode::PageDown, KeyCode::End] {
+            assert!(!press(&mut select, code));
+        }
+        select.select(0);
+        assert_eq!(select.selected(), None);
+        assert_eq!(select.visible(5), 0..0);
+    }
+}
@@ -0,0 +1,147 @@
+use crossterm::{
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    style::{self, Stylize},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::screen::Line;
+
+/// A single line of editable text with a label in front of it
+pub struct TextInput {
+    label: String,
+    value: String,
+    /// Byte index into `value`, always on a character boundary
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: String::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Starts out with `value` already typed in and the cursor at its end
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Edits the text according to the key, returns whether the key was used
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('a') if control => self.cursor = 0,
+            KeyCode::Char('e') if control => self.cursor = self.value.len(),
+            KeyCode::Char('w') if control => {
+                let start = self.previous_word_start();
+                self.value.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Char('u') if control => {
+                self.value.replace_range(..self.cursor, "");
+                self.cursor = 0;
+            }
+            KeyCode::Char(c) if !control && !key.modifiers.contains(KeyModifiers::ALT) => {
+                self.insert(&c.to_string());
+            }
+            KeyCode::Left => self.cursor = self.previous_boundary(),
+            KeyCode::Right => self.cursor = self.next_boundary(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.len(),
+            KeyCode::Backspace => {
+                let start = self.previous_boundary();
+                self.value.replace_range(start..self.cursor, "");
+                self.cursor = start;
+            }
+            KeyCode::Delete => {
+                let end = self.next_boundary();
+                self.value.replace_range(self.cursor..end, "");
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    /// Inserts pasted text at the cursor, it's a single line so line breaks are dropped
+    pub fn paste(&mut self, text: &str) {
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+        self.insert(&text);
+    }
+
+    fn insert(&mut self, text: &str) {
+        self.value.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.value[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    /// Start of the word before the cursor, skipping whitespace right before it like a shell
+    fn previous_word_start(&self) -> usize {
+        let before = self.value[..self.cursor].trim_end();
+        before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(index, c)| index + c.len_utf8())
+    }
+
+    /// Renders the label and the text, scrolled so the cursor stays within `width` columns.
+    /// Also returns the column the cursor should be shown at.
+    pub fn render(&self, width: u16) -> (Line, u16) {
+        // On a very narrow terminal the text matters more than the label
+        let label = if self.label.width() <= usize::from(width) / 2 {
+            self.label.as_str()
+        } else {
+            ""
+        };
+        let label_width = label.width();
+        let available = usize::from(width).saturating_sub(label_width + 1).max(1);
+
+        // Drop characters from the front until the cursor fits
+        let mut start = 0;
+        while self.value[start..self.cursor].width() >= available {
+            start += self.value[start..].chars().next().map_or(1, char::len_utf8);
+        }
+
+        let mut visible = String::new();
+        let mut visible_width = 0;
+        for c in self.value[start..].chars() {
+            let char_width = c.width().unwrap_or(0);
+            if visible_width + char_width > available {
+                break;
+            }
+            visible.push(c);
+            visible_width += char_width;
+        }
+
+        let cursor = label_width + self.value[start..self.cursor].width();
+        let line = vec![
+            style::style(label.to_string()).bold(),
+            style::style(visible).white(),
+        ];
+
+        (line, cursor.try_into().unwrap_or(u16::MAX))
+    }
+}